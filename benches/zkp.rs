@@ -0,0 +1,66 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion, Throughput};
+use zkp_chaum_pedersen::{Accelerator, ZKP};
+
+fn bench_group(c: &mut Criterion, name: &str, zkp: &ZKP) {
+    let x = ZKP::generate_rand_below(zkp.q());
+    let k = ZKP::generate_rand_below(zkp.q());
+    let challenge = ZKP::generate_rand_below(zkp.q());
+
+    let (y1, y2) = zkp.register_commitment(&x);
+    let (r1, r2, s) = zkp.prove(&x, &k, &challenge).unwrap();
+
+    let mut group = c.benchmark_group(name);
+
+    group.bench_function("exponentiate", |b| {
+        b.iter(|| ZKP::exponentiate(black_box(zkp.alpha()), black_box(&k), zkp.p()))
+    });
+
+    group.bench_function("solve", |b| {
+        b.iter(|| zkp.solve(black_box(&k), black_box(&challenge), black_box(&x)).unwrap())
+    });
+
+    group.bench_function("verify", |b| {
+        b.iter(|| zkp.verify(black_box(&r1), black_box(&r2), &y1, &y2, black_box(&challenge), black_box(&s)))
+    });
+
+    let accelerator = Accelerator::new(zkp);
+    group.bench_function("verify_accelerated", |b| {
+        b.iter(|| accelerator.verify(black_box(&r1), black_box(&r2), &y1, &y2, black_box(&challenge), black_box(&s)))
+    });
+
+    // one full register/prove/verify cycle per element
+    group.throughput(Throughput::Elements(1));
+    group.bench_function("prove_and_verify", |b| {
+        b.iter(|| {
+            let (y1, y2) = zkp.register_commitment(&x);
+            let k = ZKP::generate_rand_below(zkp.q());
+            let (r1, r2, s) = zkp.prove(&x, &k, &challenge).unwrap();
+            assert!(zkp.verify(&r1, &r2, &y1, &y2, &challenge, &s));
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_1024(c: &mut Criterion) {
+    bench_group(c, "rfc5114-1024", &ZKP::default_1024());
+}
+
+fn bench_2048(c: &mut Criterion) {
+    bench_group(c, "rfc5114-2048", &ZKP::default_2048());
+}
+
+/// `default_1024` clones the cached group, `from_named_group` parses the hex every call
+fn bench_group_setup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("group-setup");
+
+    group.bench_function("default_1024", |b| b.iter(ZKP::default_1024));
+    group.bench_function("parse_1024", |b| {
+        b.iter(|| ZKP::from_named_group(black_box("rfc5114-1024")).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_1024, bench_2048, bench_group_setup);
+criterion_main!(benches);
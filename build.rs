@@ -1,10 +1,18 @@
 fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // only the std feature has the gRPC service, core and wasm builds need no protoc
+    if std::env::var_os("CARGO_FEATURE_STD").is_none() {
+        return Ok(());
+    }
+
+    let out_dir = std::path::PathBuf::from(std::env::var("OUT_DIR")?);
+
     tonic_build::configure()
          .build_server(true)
          .out_dir("src/")
+         .file_descriptor_set_path(out_dir.join("zkp_auth_descriptor.bin"))
          .compile_protos(
              &["proto/zkp_auth.proto"],
              &["proto/"],
          )?;
     Ok(())
- }
\ No newline at end of file
+ }
@@ -0,0 +1,94 @@
+//! Decodes requests from arbitrary bytes and drives the `AuthImpl` handlers with
+//! them, every request must end in a response or a `Status`, never a panic
+//!
+//! The input is a list of records `op, len, len bytes`, each decoded as the
+//! protobuf message `op` selects. Both servers use the toy group so single byte
+//! elements and scalars pass decoding and the fuzzer reaches verification
+
+#![no_main]
+
+use std::sync::OnceLock;
+
+use libfuzzer_sys::fuzz_target;
+use prost::Message;
+use tokio::runtime::Runtime;
+use tonic::metadata::MetadataValue;
+use tonic::Request;
+use zkp_chaum_pedersen::service::{AuthImpl, REGISTRATION_TOKEN_KEY};
+use zkp_chaum_pedersen::zkp_auth::auth_server::Auth;
+use zkp_chaum_pedersen::zkp_auth::{AuthenticationAnswerRequest, AuthenticationChallengeRequest, RegisterRequest};
+use zkp_chaum_pedersen::ZKP;
+
+fn runtime() -> &'static Runtime {
+    static RUNTIME: OnceLock<Runtime> = OnceLock::new();
+    RUNTIME.get_or_init(|| tokio::runtime::Builder::new_current_thread().enable_all().build().unwrap())
+}
+
+/// (op, body) records, a truncated last record is dropped
+fn records(mut data: &[u8]) -> Vec<(u8, &[u8])> {
+    let mut records = Vec::new();
+    while let [op, len, rest @ ..] = data {
+        let len = (*len as usize).min(rest.len());
+        records.push((*op, &rest[..len]));
+        data = &rest[len..];
+    }
+    records
+}
+
+async fn run(data: &[u8]) {
+    // fresh servers per input so a crash reproduces from its input alone
+    let stateful = AuthImpl::default().with_zkp(ZKP::toy());
+    let stateless = AuthImpl::default().with_zkp(ZKP::toy()).with_registration_tokens(vec![7u8; 32]);
+
+    // the last auth_id and registration token handed out, used when a decoded
+    // request leaves them empty so answers can reach an open challenge
+    let mut auth_id = String::new();
+    let mut token = String::new();
+
+    for (op, body) in records(data) {
+        match op % 4 {
+            0 => {
+                let Ok(request) = RegisterRequest::decode(body) else { continue };
+                let _ = stateful.register(Request::new(request.clone())).await;
+                if let Ok(response) = stateless.register(Request::new(request)).await {
+                    token = response.into_inner().registration_token;
+                }
+            }
+            1 => {
+                let Ok(request) = AuthenticationChallengeRequest::decode(body) else { continue };
+                if let Ok(response) = stateful.create_authentication_challenge(Request::new(request.clone())).await {
+                    auth_id = response.into_inner().auth_id;
+                }
+                let mut request = Request::new(request);
+                if let Ok(value) = MetadataValue::try_from(token.as_str()) {
+                    request.metadata_mut().insert(REGISTRATION_TOKEN_KEY, value);
+                }
+                if let Ok(response) = stateless.create_authentication_challenge(request).await {
+                    auth_id = response.into_inner().auth_id;
+                }
+            }
+            2 => {
+                let Ok(mut request) = AuthenticationAnswerRequest::decode(body) else { continue };
+                if request.auth_id.is_empty() {
+                    request.auth_id = auth_id.clone();
+                }
+                let _ = stateful.verify_authentication(Request::new(request)).await;
+            }
+            _ => {
+                // the registration token is untrusted input as well: the body is
+                // the token when it is valid metadata, else the last one issued
+                let s = body.get(..1).unwrap_or_default().to_vec();
+                let mut request = Request::new(AuthenticationAnswerRequest { auth_id: auth_id.clone(), s });
+                let value = std::str::from_utf8(body).ok().and_then(|body| MetadataValue::try_from(body).ok());
+                if let Some(value) = value.or_else(|| MetadataValue::try_from(token.as_str()).ok()) {
+                    request.metadata_mut().insert(REGISTRATION_TOKEN_KEY, value);
+                }
+                let _ = stateless.verify_authentication(request).await;
+            }
+        }
+    }
+}
+
+fuzz_target!(|data: &[u8]| {
+    runtime().block_on(run(data));
+});
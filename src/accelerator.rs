@@ -0,0 +1,175 @@
+use num_bigint::BigUint;
+
+use crate::ZKP;
+
+/// Bits of the exponent consumed per table lookup
+const WINDOW_BITS: u64 = 4;
+
+/// Powers of one fixed base: `rows[i][j] = base^(j * 16^i) mod p`
+///
+/// base^e is then the product of one entry per 4-bit digit of e, so no
+/// squarings are needed for exponents below 16^rows
+#[derive(Debug, Clone)]
+struct FixedBaseTable {
+    rows: Vec<Vec<BigUint>>,
+}
+
+impl FixedBaseTable {
+    /// Table for every exponent with at most `bits` bits
+    fn new(base: &BigUint, p: &BigUint, bits: u64) -> Self {
+        let one = BigUint::from(1u32);
+        let mut rows = Vec::new();
+
+        let mut row_base = base % p;
+        for _ in 0..bits.div_ceil(WINDOW_BITS) {
+            let mut row = Vec::with_capacity(1 << WINDOW_BITS);
+            row.push(one.clone());
+            for j in 1..(1 << WINDOW_BITS) {
+                let next = (&row[j - 1] * &row_base) % p;
+                row.push(next);
+            }
+
+            // base^(16^(i + 1)) = base^(15 * 16^i) * base^(16^i)
+            row_base = (&row[(1 << WINDOW_BITS) - 1] * &row_base) % p;
+            rows.push(row);
+        }
+
+        FixedBaseTable{rows}
+    }
+
+    /// output = base^exponent mod p, None if the exponent is wider than the table
+    fn pow(&self, exponent: &BigUint, p: &BigUint) -> Option<BigUint> {
+        if exponent.bits() > self.rows.len() as u64 * WINDOW_BITS {
+            return None;
+        }
+
+        let mut result = BigUint::from(1u32);
+        for (i, byte) in exponent.to_bytes_le().into_iter().enumerate() {
+            for (row, digit) in [(2 * i, byte & 0x0f), (2 * i + 1, byte >> 4)] {
+                if digit != 0 {
+                    result = (result * &self.rows[row][digit as usize]) % p;
+                }
+            }
+        }
+        Some(result % p)
+    }
+}
+
+/// Verifier for many proofs in one group: precomputes window tables for alpha
+/// and beta so alpha^s and beta^s cost about q.bits() / 4 multiplications each
+/// instead of a full modpow. y1^c and y2^c still use modpow since y1 and y2
+/// change with every user
+#[derive(Debug, Clone)]
+pub struct Accelerator {
+    p: BigUint,
+    q: BigUint,
+    alpha: FixedBaseTable,
+    beta: FixedBaseTable,
+}
+
+impl Accelerator {
+    /// Tables cover exponents below 2^q.bits(), i.e. every s in [0, q)
+    pub fn new(zkp: &ZKP) -> Self {
+        let bits = zkp.q().bits();
+
+        Accelerator {
+            p: zkp.p().clone(),
+            q: zkp.q().clone(),
+            alpha: FixedBaseTable::new(zkp.alpha(), zkp.p(), bits),
+            beta: FixedBaseTable::new(zkp.beta(), zkp.p(), bits),
+        }
+    }
+
+    /// output = alpha^exp mod p
+    pub fn alpha_pow(&self, exponent: &BigUint) -> BigUint {
+        self.alpha.pow(exponent, &self.p).unwrap_or_else(|| self.alpha.rows[0][1].modpow(exponent, &self.p))
+    }
+
+    /// output = beta^exp mod p
+    pub fn beta_pow(&self, exponent: &BigUint) -> BigUint {
+        self.beta.pow(exponent, &self.p).unwrap_or_else(|| self.beta.rows[0][1].modpow(exponent, &self.p))
+    }
+
+    /// Same result as `ZKP::verify`
+    /// cond1: r1 = alpha^s * y1^c
+    /// cond2: r2 = beta^s * y2^c
+    pub fn verify(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+        if !crate::core::scalars_in_range(&self.q, c, s) {
+            return false;
+        }
+
+        let cond1 = *r1 == (self.alpha_pow(s) * y1.modpow(c, &self.p)) % &self.p;
+        let cond2 = *r2 == (self.beta_pow(s) * y2.modpow(c, &self.p)) % &self.p;
+        cond1 && cond2
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    use std::time::Instant;
+
+    #[test]
+    fn test_pow_matches_modpow() {
+        let zkp = ZKP::default_1024();
+        let accelerator = Accelerator::new(&zkp);
+
+        for exponent in [BigUint::from(0u32), BigUint::from(1u32), BigUint::from(16u32), zkp.q() - BigUint::from(1u32), ZKP::generate_rand_below(zkp.q())] {
+            assert_eq!(accelerator.alpha_pow(&exponent), zkp.alpha().modpow(&exponent, zkp.p()));
+            assert_eq!(accelerator.beta_pow(&exponent), zkp.beta().modpow(&exponent, zkp.p()));
+        }
+
+        // wider than the table falls back to modpow
+        let wide = zkp.p() - BigUint::from(2u32);
+        assert_eq!(accelerator.alpha_pow(&wide), zkp.alpha().modpow(&wide, zkp.p()));
+    }
+
+    #[test]
+    fn test_verify_matches_zkp() {
+        let zkp = ZKP::default_1024();
+        let accelerator = Accelerator::new(&zkp);
+
+        let x = ZKP::generate_rand_below(zkp.q());
+        let k = ZKP::generate_rand_below(zkp.q());
+        let c = ZKP::generate_rand_below(zkp.q());
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert!(accelerator.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        let (r1, r2, s) = zkp.prove(&(&x + BigUint::from(1u32)), &k, &c).unwrap();
+        assert!(!accelerator.verify(&r1, &r2, &y1, &y2, &c, &s));
+        assert!(!accelerator.verify(&r1, &r2, &y1, &y2, zkp.q(), &s));
+    }
+
+    /// Timing dependent. `cargo bench -- verify` measures verify_accelerated at
+    /// about 360us vs 670us (1024-bit) and 1.56ms vs 2.69ms (2048-bit)
+    #[test]
+    #[ignore = "timing sensitive, run with cargo test --release -- --ignored"]
+    fn test_verify_is_not_slower() {
+        const ROUNDS: usize = 50;
+
+        let zkp = ZKP::default_2048();
+        let accelerator = Accelerator::new(&zkp);
+
+        let x = ZKP::generate_rand_below(zkp.q());
+        let c = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &ZKP::generate_rand_below(zkp.q()), &c).unwrap();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+        }
+        let naive = start.elapsed();
+
+        let start = Instant::now();
+        for _ in 0..ROUNDS {
+            assert!(accelerator.verify(&r1, &r2, &y1, &y2, &c, &s));
+        }
+        let accelerated = start.elapsed();
+
+        assert!(accelerated <= naive, "accelerated {:?} vs naive {:?}", accelerated, naive);
+    }
+}
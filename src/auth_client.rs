@@ -0,0 +1,231 @@
+use std::future::Future;
+use std::time::Duration;
+
+use tonic::transport::Channel;
+use tonic::Status;
+
+use crate::service::error_code;
+use crate::zkp_auth::{auth_client::AuthClient as GrpcClient, AuthenticationAnswerRequest, AuthenticationChallengeRequest, RegisterRequest};
+use crate::{Secret, ZKPError, ZKP};
+
+/// Attempts after the first one for retryable failures unless set with `with_retries`
+pub const DEFAULT_RETRIES: u32 = 3;
+/// Pause between attempts unless set with `with_retry_delay`
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_millis(100);
+/// Limit for each call to the server unless set with `with_timeout`
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Session id returned by the server after a successful authentication
+pub type SessionId = String;
+
+/// Client for the Auth service that runs the whole protocol for one user
+///
+/// The connection is opened lazily and dropped after a transport error, so the
+/// next attempt reconnects. Failures with `ZKPError::is_retryable` are retried up
+/// to `retries` times, every `authenticate` attempt starts over with a fresh
+/// nonce k and challenge
+///
+/// Each call, connecting included, fails with `ZKPError::Timeout` after
+/// `timeout`, which is retried like a transport error on a new connection
+pub struct AuthClient {
+    addr: String,
+    zkp: ZKP,
+    client: Option<GrpcClient<Channel>>,
+    retries: u32,
+    retry_delay: Duration,
+    timeout: Duration,
+}
+
+impl AuthClient {
+    /// Client for the server at `addr`, e.g. "http://127.0.0.1:50051", using the 1024-bit group
+    pub fn new(addr: impl Into<String>) -> Self {
+        AuthClient {
+            addr: addr.into(),
+            zkp: ZKP::default_1024(),
+            client: None,
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            timeout: DEFAULT_TIMEOUT,
+        }
+    }
+
+    /// Builds proofs in `zkp` instead of the 1024-bit group, must match the server
+    pub fn with_zkp(mut self, zkp: ZKP) -> Self {
+        self.zkp = zkp;
+        self
+    }
+
+    /// Retry retryable failures `retries` times, 0 disables retrying
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    pub fn with_retry_delay(mut self, retry_delay: Duration) -> Self {
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Give up on a call to the server after `timeout`
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Group parameters used to build the proofs
+    pub fn zkp(&self) -> &ZKP {
+        &self.zkp
+    }
+
+    /// Opens the connection now instead of on the first request
+    pub async fn connect(&mut self) -> Result<(), ZKPError> {
+        self.client().await.map(|_| ())
+    }
+
+    /// Registers y1 = alpha^x mod p and y2 = beta^x mod p for `user`
+    ///
+    /// A retry after a lost response can see `UserAlreadyExists` for a registration
+    /// that went through
+    pub async fn register(&mut self, user: &str, x: &Secret) -> Result<(), ZKPError> {
+        let (y1, y2) = self.zkp.register_commitment(x);
+
+        let mut attempt = 0;
+        loop {
+            let request = RegisterRequest {
+                user: user.to_string(),
+                y1: y1.to_bytes_be(),
+                y2: y2.to_bytes_be(),
+            };
+            let timeout = self.timeout;
+            let result = with_timeout(timeout, "register", async {
+                let client = self.client().await?;
+                client.register(request).await.map(|_| ()).map_err(|status| into_error(status, user, ""))
+            })
+            .await;
+
+            match result {
+                Err(err) if self.should_retry(&err, attempt) => attempt += 1,
+                result => return result,
+            }
+            tokio::time::sleep(self.retry_delay).await;
+        }
+    }
+
+    /// Proves knowledge of `x` for `user`: sends r1, r2, receives c and answers with s
+    pub async fn authenticate(&mut self, user: &str, x: &Secret) -> Result<SessionId, ZKPError> {
+        let mut attempt = 0;
+        loop {
+            match self.try_authenticate(user, x).await {
+                Err(err) if self.should_retry(&err, attempt) => attempt += 1,
+                result => return result,
+            }
+            tokio::time::sleep(self.retry_delay).await;
+        }
+    }
+
+    async fn try_authenticate(&mut self, user: &str, x: &Secret) -> Result<SessionId, ZKPError> {
+        let k = Secret::new(ZKP::generate_rand_below(self.zkp.q()));
+        let r1 = ZKP::exponentiate(self.zkp.alpha(), &k, self.zkp.p());
+        let r2 = ZKP::exponentiate(self.zkp.beta(), &k, self.zkp.p());
+
+        let request = AuthenticationChallengeRequest {
+            user: user.to_string(),
+            r1: r1.to_bytes_be(),
+            r2: r2.to_bytes_be(),
+        };
+        let timeout = self.timeout;
+        let response = with_timeout(timeout, "create_authentication_challenge", async {
+            let client = self.client().await?;
+            client.create_authentication_challenge(request).await.map_err(|status| into_error(status, user, ""))
+        })
+        .await?
+        .into_inner();
+
+        let c = self.zkp.scalar_from_bytes(&response.c)?;
+        let s = self.zkp.solve(&k, &c, x)?;
+
+        let auth_id = response.auth_id;
+        let request = AuthenticationAnswerRequest {
+            auth_id: auth_id.clone(),
+            s: s.to_bytes_be(),
+        };
+        let response = with_timeout(timeout, "verify_authentication", async {
+            let client = self.client().await?;
+            client.verify_authentication(request).await.map_err(|status| into_error(status, user, &auth_id))
+        })
+        .await?
+        .into_inner();
+
+        Ok(response.session_id)
+    }
+
+    /// Connected client, reconnecting if the last attempt hit a transport error
+    async fn client(&mut self) -> Result<&mut GrpcClient<Channel>, ZKPError> {
+        if self.client.is_none() {
+            let client = GrpcClient::connect(self.addr.clone())
+                .await
+                .map_err(|e| ZKPError::Transport(format!("could not connect to {}: {}", self.addr, e)))?;
+            self.client = Some(client);
+        }
+        Ok(self.client.as_mut().expect("client was just connected"))
+    }
+
+    fn should_retry(&mut self, err: &ZKPError, attempt: u32) -> bool {
+        // a stalled connection is as unusable as a broken one
+        if let ZKPError::Transport(_) | ZKPError::Timeout(_) = err {
+            self.client = None;
+        }
+        err.is_retryable() && attempt < self.retries
+    }
+}
+
+/// Result of `call`, or `ZKPError::Timeout` naming `operation` once `timeout` passes
+async fn with_timeout<T>(timeout: Duration, operation: &str, call: impl Future<Output = Result<T, ZKPError>>) -> Result<T, ZKPError> {
+    tokio::time::timeout(timeout, call).await.unwrap_or_else(|_| Err(ZKPError::Timeout(operation.to_string())))
+}
+
+/// Rebuilds the `ZKPError` sent by the server from the `ERROR_CODE_KEY` metadata,
+/// statuses without it come from the transport or a server that is not this service
+fn into_error(status: Status, user: &str, auth_id: &str) -> ZKPError {
+    // drop the "<kind>: " prefix the server's Display added
+    let message = status.message();
+    let message = message.split_once(": ").map_or(message, |(_, detail)| detail).to_string();
+    match error_code(&status) {
+        Some("invalid-parameter") => ZKPError::InvalidParameter(message),
+        Some("weak-parameters") => ZKPError::WeakParameters(message),
+        Some("inconsistent-parameters") => ZKPError::InconsistentParameters(message),
+        Some("decode") => ZKPError::Decode(message),
+        Some("user-not-found") => ZKPError::UserNotFound(user.to_string()),
+        Some("user-already-exists") => ZKPError::UserAlreadyExists(user.to_string()),
+        Some("challenge-not-found") => ZKPError::ChallengeNotFound(auth_id.to_string()),
+        Some("challenge-expired") => ZKPError::ChallengeExpired(auth_id.to_string()),
+        Some("verification-failed") => ZKPError::VerificationFailed,
+        Some("nonce-reused") => ZKPError::NonceReused,
+        Some("invalid-session") => ZKPError::InvalidSession(message),
+        Some("rate-limited") => ZKPError::RateLimited(user.to_string()),
+        Some("storage") => ZKPError::Storage(message),
+        Some("timeout") => ZKPError::Timeout(message),
+        _ => ZKPError::Transport(format!("{:?}: {}", status.code(), status.message())),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_into_error() {
+        let status = Status::from(ZKPError::UserNotFound("alice".to_string()));
+        assert_eq!(into_error(status, "alice", ""), ZKPError::UserNotFound("alice".to_string()));
+
+        let status = Status::from(ZKPError::ChallengeExpired("id".to_string()));
+        assert_eq!(into_error(status, "alice", "id"), ZKPError::ChallengeExpired("id".to_string()));
+
+        assert_eq!(into_error(Status::from(ZKPError::VerificationFailed), "alice", "id"), ZKPError::VerificationFailed);
+
+        let status = Status::from(ZKPError::InvalidParameter("q must be non-zero".to_string()));
+        assert_eq!(into_error(status, "alice", ""), ZKPError::InvalidParameter("q must be non-zero".to_string()));
+        assert_eq!(into_error(Status::from(ZKPError::Timeout("register".to_string())), "alice", ""), ZKPError::Timeout("register".to_string()));
+        assert!(matches!(into_error(Status::unavailable("down"), "alice", ""), ZKPError::Transport(_)));
+    }
+}
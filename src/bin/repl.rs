@@ -0,0 +1,175 @@
+use std::io::{self, BufRead, Write};
+
+use num_bigint::BigUint;
+use zkp_chaum_pedersen::ZKP;
+
+const HELP: &str = "\
+commands:
+  group toy|1024|2048   switch group, clears every value
+  set x|k|c <n>         set a value, decimal
+  commit                y1, y2 from x and r1, r2 from k (random k if unset)
+  challenge [c]         set c, random in [1, q) if omitted
+  respond               s = k - c*x mod q
+  verify                check r1 = alpha^s * y1^c and r2 = beta^s * y2^c
+  show                  print the group and every value set so far
+  help
+  quit";
+
+/// One run of the protocol stepped through by hand, both prover and verifier side
+struct Repl {
+    zkp: ZKP,
+    x: Option<BigUint>,
+    k: Option<BigUint>,
+    c: Option<BigUint>,
+    s: Option<BigUint>,
+    /// (y1, y2, r1, r2)
+    commitments: Option<(BigUint, BigUint, BigUint, BigUint)>,
+}
+
+impl Repl {
+    fn new(zkp: ZKP) -> Self {
+        Repl{zkp, x: None, k: None, c: None, s: None, commitments: None}
+    }
+
+    /// Output of one command line, `Err` for input that could not be run
+    fn run(&mut self, line: &str) -> Result<String, String> {
+        let words: Vec<&str> = line.split_whitespace().collect();
+
+        match words.as_slice() {
+            [] => Ok(String::new()),
+            ["help"] => Ok(HELP.to_string()),
+            ["group", name] => {
+                let zkp = match *name {
+                    "toy" => ZKP::toy(),
+                    "1024" => ZKP::default_1024(),
+                    "2048" => ZKP::default_2048(),
+                    _ => return Err(format!("unknown group {}, expected toy, 1024 or 2048", name)),
+                };
+                *self = Repl::new(zkp);
+                Ok(self.describe_group())
+            }
+            ["set", name, value] => {
+                let value = BigUint::parse_bytes(value.as_bytes(), 10).ok_or_else(|| format!("{} is not a decimal number", value))?;
+                match *name {
+                    "x" => self.x = Some(value),
+                    "k" => self.k = Some(value),
+                    "c" => self.c = Some(value),
+                    _ => return Err(format!("unknown value {}, expected x, k or c", name)),
+                }
+                // values derived from the old one no longer apply
+                self.s = None;
+                if *name != "c" {
+                    self.commitments = None;
+                }
+                Ok(format!("{} = {}", name, words[2]))
+            }
+            ["commit"] => {
+                let x = self.x.as_ref().ok_or("set x first")?;
+                let k = self.k.get_or_insert_with(|| ZKP::generate_rand_below(self.zkp.q()));
+
+                let (y1, y2) = self.zkp.register_commitment(x);
+                let (r1, r2) = self.zkp.register_commitment(k);
+                let output = format!("k = {}\ny1 = {}\ny2 = {}\nr1 = {}\nr2 = {}", k, y1, y2, r1, r2);
+                self.commitments = Some((y1, y2, r1, r2));
+                Ok(output)
+            }
+            ["challenge"] => {
+                let c = self.zkp.generate_challenge();
+                self.run(&format!("set c {}", c))
+            }
+            ["challenge", c] => self.run(&format!("set c {}", c)),
+            ["respond"] => {
+                let (x, k, c) = match (&self.x, &self.k, &self.c) {
+                    (Some(x), Some(k), Some(c)) => (x, k, c),
+                    _ => return Err("set x, k and c first".to_string()),
+                };
+                let s = self.zkp.solve(k, c, x).map_err(|e| e.to_string())?;
+                let output = format!("s = {}", s);
+                self.s = Some(s);
+                Ok(output)
+            }
+            ["verify"] => {
+                let (y1, y2, r1, r2) = self.commitments.as_ref().ok_or("run commit first")?;
+                let (c, s) = match (&self.c, &self.s) {
+                    (Some(c), Some(s)) => (c, s),
+                    _ => return Err("run challenge and respond first".to_string()),
+                };
+                Ok(format!("{:?}", self.zkp.verify_detailed(r1, r2, y1, y2, c, s)))
+            }
+            ["show"] => {
+                let mut output = self.describe_group();
+                for (name, value) in [("x", &self.x), ("k", &self.k), ("c", &self.c), ("s", &self.s)] {
+                    if let Some(value) = value {
+                        output += &format!("\n{} = {}", name, value);
+                    }
+                }
+                if let Some((y1, y2, r1, r2)) = &self.commitments {
+                    output += &format!("\ny1 = {}\ny2 = {}\nr1 = {}\nr2 = {}", y1, y2, r1, r2);
+                }
+                Ok(output)
+            }
+            _ => Err(format!("unknown command {}, try help", line.trim())),
+        }
+    }
+
+    fn describe_group(&self) -> String {
+        format!("p = {}, q = {}, alpha = {}, beta = {}", self.zkp.p(), self.zkp.q(), self.zkp.alpha(), self.zkp.beta())
+    }
+}
+
+/// usage: repl, then one command per line, see `HELP`
+/// starts in the toy group so every value is small enough to check by hand
+fn main() {
+    let mut repl = Repl::new(ZKP::toy());
+    println!("{}\n{}", repl.describe_group(), HELP);
+
+    let stdin = io::stdin();
+    loop {
+        print!("> ");
+        io::stdout().flush().expect("could not flush stdout");
+
+        let mut line = String::new();
+        if stdin.lock().read_line(&mut line).expect("could not read from stdin") == 0 || line.trim() == "quit" {
+            break;
+        }
+        match repl.run(&line) {
+            Ok(output) if output.is_empty() => {}
+            Ok(output) => println!("{}", output),
+            Err(msg) => eprintln!("{}", msg),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    /// Same values as `test_toy_example` in the library
+    #[test]
+    fn test_toy_example() {
+        let mut repl = Repl::new(ZKP::toy());
+        repl.run("set x 6").unwrap();
+        repl.run("set k 7").unwrap();
+
+        assert_eq!(repl.run("commit").unwrap(), "k = 7\ny1 = 2\ny2 = 3\nr1 = 8\nr2 = 4");
+        assert_eq!(repl.run("challenge 4").unwrap(), "c = 4");
+        assert_eq!(repl.run("respond").unwrap(), "s = 5");
+        assert_eq!(repl.run("verify").unwrap(), "Ok");
+
+        repl.run("set c 2").unwrap();
+        assert!(repl.run("verify").is_err());
+        repl.run("respond").unwrap();
+        assert_eq!(repl.run("verify").unwrap(), "Ok");
+    }
+
+    #[test]
+    fn test_bad_input() {
+        let mut repl = Repl::new(ZKP::toy());
+        assert!(repl.run("commit").is_err());
+        assert!(repl.run("set x six").is_err());
+        assert!(repl.run("set y 6").is_err());
+        assert!(repl.run("group 512").is_err());
+        assert!(repl.run("frobnicate").is_err());
+        assert_eq!(repl.run("  ").unwrap(), "");
+    }
+}
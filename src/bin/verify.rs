@@ -0,0 +1,72 @@
+use std::fs;
+use std::path::Path;
+use std::process;
+
+use zkp_chaum_pedersen::proof::Transcript;
+use zkp_chaum_pedersen::{VerifyOutcome, ZKPError, ZKP};
+
+/// Outcome of checking the JSON `Transcript` at `path` in the named group
+fn verify_file<P: AsRef<Path>>(path: P, group: &str) -> Result<VerifyOutcome, ZKPError> {
+    let zkp = ZKP::from_named_group(group)?;
+    let json = fs::read_to_string(path.as_ref()).map_err(|e| ZKPError::Storage(format!("{}: {}", path.as_ref().display(), e)))?;
+    let transcript: Transcript = serde_json::from_str(&json).map_err(|e| ZKPError::Decode(e.to_string()))?;
+    Ok(zkp.verify_transcript(&transcript))
+}
+
+/// usage: verify <transcript.json> [group]
+/// checks a recorded proof offline, group is one of `ZKP::GROUP_NAMES` and
+/// defaults to the first. Exits 0 if the proof verifies and 1 otherwise
+fn main() {
+    let mut args = std::env::args().skip(1);
+    let Some(path) = args.next() else {
+        eprintln!("usage: verify <transcript.json> [{}]", ZKP::GROUP_NAMES.join("|"));
+        process::exit(1);
+    };
+    let group = args.next().unwrap_or_else(|| ZKP::GROUP_NAMES[0].to_string());
+
+    match verify_file(&path, &group) {
+        Ok(VerifyOutcome::Ok) => println!("{}: Ok", path),
+        Ok(outcome) => {
+            println!("{}: {:?}", path, outcome);
+            process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("{}: {}", path, e);
+            process::exit(1);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_verify_file() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (k, c) = (zkp.generate_challenge(), zkp.generate_challenge());
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        let transcript = Transcript { y1, y2, r1, r2, c, s };
+
+        let dir = tempfile::tempdir().unwrap();
+        let valid = dir.path().join("valid.json");
+        fs::write(&valid, serde_json::to_string(&transcript).unwrap()).unwrap();
+        assert_eq!(verify_file(&valid, "rfc5114-1024"), Ok(VerifyOutcome::Ok));
+        // the same numbers mean nothing in another group
+        assert_ne!(verify_file(&valid, "rfc5114-2048"), Ok(VerifyOutcome::Ok));
+
+        let invalid = dir.path().join("invalid.json");
+        let tampered = Transcript { s: (&transcript.s + 1u32) % zkp.q(), ..transcript };
+        fs::write(&invalid, serde_json::to_string(&tampered).unwrap()).unwrap();
+        assert_eq!(verify_file(&invalid, "rfc5114-1024"), Ok(VerifyOutcome::BothFailed));
+
+        let malformed = dir.path().join("malformed.json");
+        fs::write(&malformed, r#"{"y1": "zz"}"#).unwrap();
+        assert!(matches!(verify_file(&malformed, "rfc5114-1024"), Err(ZKPError::Decode(_))));
+        assert!(matches!(verify_file(dir.path().join("missing.json"), "rfc5114-1024"), Err(ZKPError::Storage(_))));
+        assert!(matches!(verify_file(&valid, "toy"), Err(ZKPError::InvalidParameter(_))));
+
+    }
+}
@@ -0,0 +1,90 @@
+use num_bigint::BigUint;
+
+use crate::{ZKPError, ZKP};
+
+/// Fluent construction of a `ZKP`
+///
+/// ```
+/// use zkp_chaum_pedersen::ZKPBuilder;
+///
+/// let zkp = ZKPBuilder::new().group("rfc5114-2048").require_secure(true).build().unwrap();
+/// assert_eq!(zkp.p().bits(), 2048);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ZKPBuilder {
+    source: Option<Source>,
+    require_secure: bool,
+}
+
+#[derive(Debug, Clone)]
+enum Source {
+    Named(String),
+    Params { p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint },
+}
+
+impl ZKPBuilder {
+    pub fn new() -> Self {
+        ZKPBuilder::default()
+    }
+
+    /// Group from `ZKP::from_named_group`, replaces earlier `group`/`params` calls
+    pub fn group(mut self, name: &str) -> Self {
+        self.source = Some(Source::Named(name.to_string()));
+        self
+    }
+
+    /// Explicit parameters checked like `ZKP::new`, replaces earlier `group`/`params` calls
+    pub fn params(mut self, p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint) -> Self {
+        self.source = Some(Source::Params{p, q, alpha, beta});
+        self
+    }
+
+    /// Also apply the size checks of `ZKP::new_secure`, off by default
+    pub fn require_secure(mut self, require_secure: bool) -> Self {
+        self.require_secure = require_secure;
+        self
+    }
+
+    /// `InvalidParameter` if neither `group` nor `params` was called
+    pub fn build(self) -> Result<ZKP, ZKPError> {
+        let (p, q, alpha, beta) = match self.source {
+            Some(Source::Named(name)) => {
+                let zkp = ZKP::from_named_group(&name)?;
+                (zkp.p().clone(), zkp.q().clone(), zkp.alpha().clone(), zkp.beta().clone())
+            }
+            Some(Source::Params{p, q, alpha, beta}) => (p, q, alpha, beta),
+            None => return Err(ZKPError::InvalidParameter("no group or parameters given".to_string())),
+        };
+
+        if self.require_secure {
+            ZKP::new_secure(p, q, alpha, beta)
+        } else {
+            ZKP::new(p, q, alpha, beta)
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_named_group_with_secure_checks() {
+        let zkp = ZKPBuilder::new().group("rfc5114-2048").require_secure(true).build().unwrap();
+        assert_eq!(zkp.p(), ZKP::default_2048().p());
+
+        let weak = ZKPBuilder::new().group("rfc5114-1024").require_secure(true).build();
+        assert!(matches!(weak, Err(ZKPError::WeakParameters(_))));
+        assert!(ZKPBuilder::new().group("rfc5114-1024").build().is_ok());
+    }
+
+    #[test]
+    fn test_params() {
+        let toy = || ZKPBuilder::new().params(BigUint::from(23u32), BigUint::from(11u32), BigUint::from(4u32), BigUint::from(9u32));
+        assert_eq!(toy().build().unwrap().q(), &BigUint::from(11u32));
+        assert!(matches!(toy().require_secure(true).build(), Err(ZKPError::WeakParameters(_))));
+
+        assert!(matches!(ZKPBuilder::new().require_secure(true).build(), Err(ZKPError::InvalidParameter(_))));
+        assert!(ZKPBuilder::new().group("unknown").build().is_err());
+    }
+}
@@ -0,0 +1,94 @@
+use std::io::{self, BufRead, Write};
+use std::process;
+
+use num_bigint::BigUint;
+use zkp_chaum_pedersen::auth_client::AuthClient;
+use zkp_chaum_pedersen::{Secret, ZKP};
+
+const DEFAULT_ADDR: &str = "http://127.0.0.1:50051";
+
+/// Reads one trimmed line from stdin after printing `prompt`
+fn prompt(prompt: &str) -> String {
+    print!("{}", prompt);
+    io::stdout().flush().expect("could not flush stdout");
+
+    let mut line = String::new();
+    io::stdin().lock().read_line(&mut line).expect("could not read from stdin");
+    line.trim().to_string()
+}
+
+fn fail(msg: String) -> ! {
+    eprintln!("{}", msg);
+    process::exit(1);
+}
+
+/// Output of `client keygen` for the secret `x`: x in decimal, as the login
+/// prompt expects it, and the commitments y1, y2 in hex
+fn keygen(zkp: &ZKP, x: &Secret) -> String {
+    let (y1, y2) = zkp.register_commitment(x);
+    format!("x = {}\ny1 = {}\ny2 = {}", **x, hex::encode(y1.to_bytes_be()), hex::encode(y2.to_bytes_be()))
+}
+
+/// usage: client [user] [x] [addr]
+/// user and x are prompted for when missing
+///
+/// usage: client keygen
+/// prints a fresh x in [1, q) of the 1024-bit group with its y1 and y2
+#[tokio::main]
+async fn main() {
+    if std::env::args().nth(1).as_deref() == Some("keygen") {
+        let zkp = ZKP::default_1024();
+        let x = Secret::new(zkp.generate_challenge());
+        eprintln!("WARNING: x is your password, store it somewhere safe and never share it. Anyone who knows x can log in as you and a lost x cannot be recovered");
+        println!("{}", keygen(&zkp, &x));
+        return;
+    }
+
+    let mut args = std::env::args().skip(1);
+    let user = args.next().unwrap_or_else(|| prompt("Please provide the username: "));
+    let secret = args.next().unwrap_or_else(|| prompt("Please provide the secret x: "));
+    let addr = args.next().unwrap_or_else(|| DEFAULT_ADDR.to_string());
+
+    let x = BigUint::parse_bytes(secret.as_bytes(), 10).unwrap_or_else(|| fail("x must be a decimal number".to_string()));
+    let x = Secret::new(x);
+
+    let mut client = AuthClient::new(addr.clone());
+    client
+        .connect()
+        .await
+        .unwrap_or_else(|e| fail(format!("could not connect to the server at {}: {}", addr, e)));
+    println!("Connected to the server at {}", addr);
+
+    client.register(&user, &x).await.unwrap_or_else(|e| fail(format!("could not register {}: {}", user, e)));
+    println!("Registered {}", user);
+
+    let session_id = client.authenticate(&user, &x).await.unwrap_or_else(|e| fail(format!("could not authenticate: {}", e)));
+
+    println!("Logged in! session id: {}", session_id);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_keygen() {
+        let zkp = ZKP::toy();
+        let x = Secret::new(BigUint::from(6u32));
+        assert_eq!(keygen(&zkp, &x), "x = 6\ny1 = 02\ny2 = 03");
+
+        // the printed values complete a login for that x
+        let zkp = ZKP::default_1024();
+        let x = Secret::new(zkp.generate_challenge());
+        let output = keygen(&zkp, &x);
+        let values: Vec<&str> = output.lines().map(|line| line.split(" = ").nth(1).unwrap()).collect();
+        let printed_x = BigUint::parse_bytes(values[0].as_bytes(), 10).unwrap();
+        let y1 = zkp.element_from_bytes(&hex::decode(values[1]).unwrap()).unwrap();
+        let y2 = zkp.element_from_bytes(&hex::decode(values[2]).unwrap()).unwrap();
+        assert_eq!(printed_x, *x);
+
+        let (k, c) = (zkp.generate_challenge(), zkp.generate_challenge());
+        let (r1, r2, s) = zkp.prove(&printed_x, &k, &c).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+}
@@ -0,0 +1,150 @@
+//! Server settings from a JSON file and environment variables
+//!
+//! Every field is optional in the file, missing ones keep the defaults of
+//! `ServerConfig::default`. Sample:
+//! {"addr": "0.0.0.0:50051", "group": "rfc5114-2048", "challenge_ttl_secs": 60, "store_path": "users.json"}
+
+use std::fs;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::service::{AuthImpl, DEFAULT_CHALLENGE_TTL};
+use crate::store::{InMemoryUserStore, JsonFileUserStore, UserStore};
+use crate::{ZKPError, ZKP};
+
+/// Address the server listens on unless configured otherwise
+pub const DEFAULT_ADDR: &str = "127.0.0.1:50051";
+
+/// Environment variables read by `ServerConfig::with_env`, each overriding the
+/// field of the same name
+pub const ENV_ADDR: &str = "ZKP_ADDR";
+pub const ENV_GROUP: &str = "ZKP_GROUP";
+pub const ENV_CHALLENGE_TTL_SECS: &str = "ZKP_CHALLENGE_TTL_SECS";
+pub const ENV_STORE_PATH: &str = "ZKP_STORE_PATH";
+
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ServerConfig {
+    pub addr: SocketAddr,
+    /// One of `ZKP::GROUP_NAMES`
+    pub group: String,
+    pub challenge_ttl_secs: u64,
+    /// File for a `JsonFileUserStore`, registrations only live in memory when unset
+    pub store_path: Option<PathBuf>,
+}
+
+impl Default for ServerConfig {
+    fn default() -> Self {
+        ServerConfig {
+            addr: DEFAULT_ADDR.parse().expect("DEFAULT_ADDR is a socket address"),
+            group: ZKP::GROUP_NAMES[0].to_string(),
+            challenge_ttl_secs: DEFAULT_CHALLENGE_TTL.as_secs(),
+            store_path: None,
+        }
+    }
+}
+
+impl ServerConfig {
+    /// `Decode` for malformed JSON, unknown fields included
+    pub fn from_json(json: &str) -> Result<Self, ZKPError> {
+        serde_json::from_str(json).map_err(|e| ZKPError::Decode(e.to_string()))
+    }
+
+    /// Config in the JSON file at `path`, `Storage` if it cannot be read
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, ZKPError> {
+        let json = fs::read_to_string(path.as_ref()).map_err(|e| ZKPError::Storage(format!("{}: {}", path.as_ref().display(), e)))?;
+        ServerConfig::from_json(&json)
+    }
+
+    /// Overrides fields from the `ENV_*` variables that are set
+    pub fn with_env(self) -> Result<Self, ZKPError> {
+        self.with_vars(|key| std::env::var(key).ok())
+    }
+
+    /// Same as `with_env` with the variables looked up in `var`
+    pub fn with_vars(mut self, var: impl Fn(&str) -> Option<String>) -> Result<Self, ZKPError> {
+        if let Some(addr) = var(ENV_ADDR) {
+            self.addr = addr.parse().map_err(|_| ZKPError::Decode(format!("{} is not a socket address: {}", ENV_ADDR, addr)))?;
+        }
+        if let Some(group) = var(ENV_GROUP) {
+            self.group = group;
+        }
+        if let Some(ttl) = var(ENV_CHALLENGE_TTL_SECS) {
+            self.challenge_ttl_secs = ttl.parse().map_err(|_| ZKPError::Decode(format!("{} is not a number of seconds: {}", ENV_CHALLENGE_TTL_SECS, ttl)))?;
+        }
+        if let Some(path) = var(ENV_STORE_PATH) {
+            self.store_path = Some(PathBuf::from(path));
+        }
+        Ok(self)
+    }
+
+    pub fn challenge_ttl(&self) -> Duration {
+        Duration::from_secs(self.challenge_ttl_secs)
+    }
+
+    /// `AuthImpl` for the configured group, user store and challenge TTL
+    pub fn auth_impl(&self) -> Result<AuthImpl, ZKPError> {
+        let zkp = ZKP::from_named_group(&self.group)?;
+        let store: Box<dyn UserStore> = match &self.store_path {
+            Some(path) => Box::new(JsonFileUserStore::open(path)?),
+            None => Box::new(InMemoryUserStore::default()),
+        };
+
+        Ok(AuthImpl::new(zkp, store).with_challenge_ttl(self.challenge_ttl()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_from_json() {
+        let json = r#"{"addr": "0.0.0.0:6000", "group": "rfc5114-2048", "challenge_ttl_secs": 30, "store_path": "/var/lib/zkp/users.json"}"#;
+        let config = ServerConfig::from_json(json).unwrap();
+        assert_eq!(config, ServerConfig {
+            addr: "0.0.0.0:6000".parse().unwrap(),
+            group: "rfc5114-2048".to_string(),
+            challenge_ttl_secs: 30,
+            store_path: Some(PathBuf::from("/var/lib/zkp/users.json")),
+        });
+        assert_eq!(config.challenge_ttl(), Duration::from_secs(30));
+
+        // missing fields keep their defaults
+        let config = ServerConfig::from_json(r#"{"group": "rfc5114-2048"}"#).unwrap();
+        assert_eq!(config, ServerConfig { group: "rfc5114-2048".to_string(), ..ServerConfig::default() });
+        assert_eq!(ServerConfig::from_json("{}").unwrap(), ServerConfig::default());
+
+        assert!(matches!(ServerConfig::from_json(r#"{"challenge_ttl": 30}"#), Err(ZKPError::Decode(_))));
+        assert!(matches!(ServerConfig::from_json(r#"{"addr": "localhost"}"#), Err(ZKPError::Decode(_))));
+    }
+
+    #[test]
+    fn test_env_overrides() {
+        let vars = |key: &str| match key {
+            ENV_ADDR => Some("10.0.0.1:7000".to_string()),
+            ENV_CHALLENGE_TTL_SECS => Some("5".to_string()),
+            _ => None,
+        };
+        let config = ServerConfig::default().with_vars(vars).unwrap();
+        assert_eq!(config.addr, "10.0.0.1:7000".parse().unwrap());
+        assert_eq!(config.challenge_ttl_secs, 5);
+        assert_eq!(config.group, ServerConfig::default().group);
+
+        let bad_ttl = |key: &str| (key == ENV_CHALLENGE_TTL_SECS).then(|| "soon".to_string());
+        assert!(matches!(ServerConfig::default().with_vars(bad_ttl), Err(ZKPError::Decode(_))));
+    }
+
+    #[test]
+    fn test_auth_impl() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ServerConfig { group: "rfc5114-2048".to_string(), store_path: Some(dir.path().join("users.json")), ..ServerConfig::default() };
+        assert_eq!(config.auth_impl().unwrap().zkp(), &ZKP::default_2048());
+
+        let config = ServerConfig { group: "rfc5114-512".to_string(), ..ServerConfig::default() };
+        assert!(matches!(config.auth_impl(), Err(ZKPError::InvalidParameter(_))));
+    }
+}
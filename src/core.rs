@@ -0,0 +1,161 @@
+//! Prove/verify arithmetic on plain `BigUint`s, builds without `std`
+//!
+//! `ZKP` wraps these with the group parameters, randomness and encodings
+
+use alloc::string::ToString;
+use alloc::vec::Vec;
+
+use num_bigint::{BigInt, BigUint};
+
+use crate::ZKPError;
+
+/// output = n^exp mod modulus
+pub fn exponentiate(n: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
+    n.modpow(exponent, modulus)
+}
+
+/// output = s = k - c*x mod q
+///
+/// k, c and x are reduced mod q first, so the output is always in [0, q). Any
+/// x is accepted and the effective witness is x mod q: alpha has order q, so a
+/// full-width x, e.g. derived from a password, has y1 = alpha^(x mod q) as well
+pub fn solve(k: &BigUint, c: &BigUint, x: &BigUint, q: &BigUint) -> Result<BigUint, ZKPError> {
+    if *q == BigUint::from(0u32) {
+        return Err(ZKPError::InvalidParameter("q must be non-zero".to_string()));
+    }
+
+    let q_int = BigInt::from(q.clone());
+    let k = BigInt::from(reduce(k, q));
+    let cx = BigInt::from(reduce(c, q) * reduce(x, q));
+
+    let s = ((k - cx) % &q_int + &q_int) % &q_int;
+    Ok(s.to_biguint().expect("s is reduced into [0, q)"))
+}
+
+/// cond: c in [1, q) and s in [0, q)
+///
+/// c = 0 reduces the check to r = g^s, which anyone can satisfy without x.
+/// s = 0 is an honest answer whenever k = c*x mod q and is accepted
+pub fn scalars_in_range(q: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+    *c != BigUint::from(0u32) && c < q && s < q
+}
+
+/// One relation of the proof for generator g and y = g^x mod p
+/// cond: `scalars_in_range(q, c, s)` and r = g^s * y^c mod p
+///
+/// Chaum-Pedersen checks it for (alpha, y1, r1) and (beta, y2, r2)
+pub fn verify(p: &BigUint, q: &BigUint, g: &BigUint, y: &BigUint, r: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+    if !scalars_in_range(q, c, s) {
+        return false;
+    }
+
+    *r == reduce(&(g.modpow(s, p) * y.modpow(c, p)), p)
+}
+
+/// output = s_i = k_i - c*x_i mod q for every i, one challenge c for the whole vector
+///
+/// Errors if k and x differ in length or q = 0
+pub fn solve_vec(k: &[BigUint], c: &BigUint, x: &[BigUint], q: &BigUint) -> Result<Vec<BigUint>, ZKPError> {
+    if k.len() != x.len() {
+        return Err(ZKPError::InvalidParameter("k and x differ in length".to_string()));
+    }
+    k.iter().zip(x).map(|(k, x)| solve(k, c, x, q)).collect()
+}
+
+/// Proof of knowledge of (x_1..x_n) with y_i = g_i^x_i mod p, r_i = g_i^k_i mod p
+/// cond: `verify(p, q, g_i, y_i, r_i, c, s_i)` for every i
+///
+/// Empty slices or slices of different lengths are rejected
+pub fn verify_vec(p: &BigUint, q: &BigUint, generators: &[BigUint], y: &[BigUint], r: &[BigUint], c: &BigUint, s: &[BigUint]) -> bool {
+    let n = generators.len();
+    if n == 0 || y.len() != n || r.len() != n || s.len() != n {
+        return false;
+    }
+
+    (0..n).all(|i| verify(p, q, &generators[i], &y[i], &r[i], c, &s[i]))
+}
+
+/// output = value mod modulus, same as `value.modpow(1, modulus)` without the exponentiation
+fn reduce(value: &BigUint, modulus: &BigUint) -> BigUint {
+    value % modulus
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_support::bn_from_hex;
+
+    #[test]
+    fn test_toy_example() {
+        let (p, q, alpha) = (BigUint::from(23u32), BigUint::from(11u32), BigUint::from(4u32));
+        let (x, k, c) = (BigUint::from(6u32), BigUint::from(7u32), BigUint::from(4u32));
+
+        let y = exponentiate(&alpha, &x, &p);
+        let r = exponentiate(&alpha, &k, &p);
+        let s = solve(&k, &c, &x, &q).unwrap();
+        assert_eq!(s, BigUint::from(5u32));
+
+        assert!(verify(&p, &q, &alpha, &y, &r, &c, &s));
+        assert!(!verify(&p, &q, &alpha, &y, &r, &c, &(&s + &q)));
+        assert!(solve(&k, &c, &x, &BigUint::from(0u32)).is_err());
+
+        // with c = 0, r = alpha^s passes the relation for any y
+        let zero = BigUint::from(0u32);
+        let r = exponentiate(&alpha, &s, &p);
+        assert!(!verify(&p, &q, &alpha, &y, &r, &zero, &s));
+
+        // s = 0 is the honest answer for k = c*x mod q = 2
+        let k = BigUint::from(2u32);
+        assert_eq!(solve(&k, &c, &x, &q).unwrap(), zero);
+        assert!(verify(&p, &q, &alpha, &y, &exponentiate(&alpha, &k, &p), &c, &zero));
+    }
+
+    #[test]
+    fn test_vector_proof() {
+        let (p, q, c) = (BigUint::from(23u32), BigUint::from(11u32), BigUint::from(4u32));
+        // 4, 9 and 2 all have order 11 mod 23
+        let generators = [4u32, 9, 2].map(BigUint::from);
+        let x = [6u32, 3, 8].map(BigUint::from);
+        let k = [7u32, 2, 9].map(BigUint::from);
+
+        let y: Vec<BigUint> = generators.iter().zip(&x).map(|(g, x)| exponentiate(g, x, &p)).collect();
+        let r: Vec<BigUint> = generators.iter().zip(&k).map(|(g, k)| exponentiate(g, k, &p)).collect();
+        let s = solve_vec(&k, &c, &x, &q).unwrap();
+        assert_eq!(s[0], BigUint::from(5u32));
+        assert!(verify_vec(&p, &q, &generators, &y, &r, &c, &s));
+
+        // one corrupted element fails the whole vector
+        for i in 0..3 {
+            let mut bad_s = s.clone();
+            bad_s[i] = (&bad_s[i] + 1u32) % &q;
+            assert!(!verify_vec(&p, &q, &generators, &y, &r, &c, &bad_s));
+
+            let mut bad_y = y.clone();
+            bad_y[i] = (&bad_y[i] * &generators[i]) % &p;
+            assert!(!verify_vec(&p, &q, &generators, &bad_y, &r, &c, &s));
+        }
+
+        assert!(!verify_vec(&p, &q, &generators, &y[..2], &r, &c, &s));
+        assert!(!verify_vec(&p, &q, &[], &[], &[], &c, &[]));
+        assert!(solve_vec(&k, &c, &x[..2], &q).is_err());
+    }
+
+    #[test]
+    fn test_reduce_matches_modpow_by_one() {
+        let one = BigUint::from(1u32);
+        let toy_q = BigUint::from(11u32);
+        let q_1024 = bn_from_hex("F518AA8781A8DF278ABA4E7D64B7CB9D49462353");
+
+        for q in [&toy_q, &q_1024] {
+            for value in [BigUint::from(0u32), BigUint::from(7u32), q - &one, q.clone(), q * q + &one, q_1024.pow(3) + BigUint::from(12345u32)] {
+                assert_eq!(reduce(&value, q), value.modpow(&one, q));
+            }
+        }
+
+        // s for the toy example and the first 1024-bit vector in vectors/rfc5114-1024.json
+        let (x, k, c) = (BigUint::from(6u32), BigUint::from(7u32), BigUint::from(4u32));
+        assert_eq!(solve(&k, &c, &x, &toy_q).unwrap(), BigUint::from(5u32));
+        let (x, k, c) = (BigUint::from(1u32), BigUint::from(2u32), BigUint::from(3u32));
+        assert_eq!(solve(&k, &c, &x, &q_1024).unwrap(), &q_1024 - &one);
+    }
+}
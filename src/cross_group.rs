@@ -0,0 +1,156 @@
+//! Chaum-Pedersen across two groups with the same subgroup order q
+//!
+//! Proves log_g1(y1) = log_g2(y2) where g1 is an element mod p1 and g2 one mod
+//! p2, both of order q. `ZKP` is the special case p1 = p2. k, c and s are
+//! scalars mod q shared by both relations, each element stays in its own group:
+//! cond1: r1 = g1^s * y1^c mod p1
+//! cond2: r2 = g2^s * y2^c mod p2
+//!
+//! A common q is what makes this sound: s = k - c*x mod q only says the same
+//! thing about x in both groups when x is only defined mod q in both
+
+use num_bigint::BigUint;
+
+use crate::{core, is_probably_prime, ZKPError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CrossGroupZKP {
+    p1: BigUint,
+    g1: BigUint,
+    p2: BigUint,
+    g2: BigUint,
+    q: BigUint,
+}
+
+impl CrossGroupZKP {
+    /// Builds a `CrossGroupZKP` after checking that q is probably prime and,
+    /// for (p1, g1) and (p2, g2), that p is probably prime, q divides p - 1
+    /// and g has order q mod p
+    ///
+    /// A composite q is rejected: extracting x from two answers divides by the
+    /// difference of their challenges mod q, which needs every nonzero c to be
+    /// invertible
+    pub fn new(p1: BigUint, g1: BigUint, p2: BigUint, g2: BigUint, q: BigUint) -> Result<Self, ZKPError> {
+        if !is_probably_prime(&q, 32) {
+            return Err(ZKPError::InvalidParameter("q is not prime".to_string()));
+        }
+        for (name, p, g) in [("1", &p1, &g1), ("2", &p2, &g2)] {
+            check_group(name, p, g, &q)?;
+        }
+        Ok(CrossGroupZKP{p1, g1, p2, g2, q})
+    }
+
+    /// Order of g1 and g2
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    /// output = (y1, y2) = (g1^x mod p1, g2^x mod p2)
+    pub fn register_commitment(&self, x: &BigUint) -> (BigUint, BigUint) {
+        (core::exponentiate(&self.g1, x, &self.p1), core::exponentiate(&self.g2, x, &self.p2))
+    }
+
+    /// output = (r1, r2, s) with r1 = g1^k mod p1, r2 = g2^k mod p2, s = k - c*x mod q
+    pub fn prove(&self, x: &BigUint, k: &BigUint, c: &BigUint) -> Result<(BigUint, BigUint, BigUint), ZKPError> {
+        let (r1, r2) = self.register_commitment(k);
+        let s = core::solve(k, c, x, &self.q)?;
+        Ok((r1, r2, s))
+    }
+
+    /// cond1: r1 = g1^s * y1^c mod p1
+    /// cond2: r2 = g2^s * y2^c mod p2
+    ///
+    /// c outside [1, q) and s outside [0, q) are rejected as in `ZKP::verify`
+    pub fn verify(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+        let cond1 = core::verify(&self.p1, &self.q, &self.g1, y1, r1, c, s);
+        let cond2 = core::verify(&self.p2, &self.q, &self.g2, y2, r2, c, s);
+        cond1 && cond2
+    }
+}
+
+fn check_group(name: &str, p: &BigUint, g: &BigUint, q: &BigUint) -> Result<(), ZKPError> {
+    let one = BigUint::from(1u32);
+
+    if !is_probably_prime(p, 32) {
+        return Err(ZKPError::InvalidParameter(format!("p{} is not prime", name)));
+    }
+    if *q <= one {
+        return Err(ZKPError::InvalidParameter("q must be greater than 1".to_string()));
+    }
+    if q >= p || (p - &one) % q != BigUint::from(0u32) {
+        return Err(ZKPError::InconsistentParameters(format!("q does not divide p{} - 1", name)));
+    }
+    if *g == one || g.modpow(q, p) != one {
+        return Err(ZKPError::InvalidParameter(format!("g{} does not have order q", name)));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZKP;
+
+    /// q = 11 divides both 23 - 1 and 67 - 1
+    /// g1 = 4 mod 23 and g2 = 2^6 = 64 mod 67 have order 11
+    fn toy() -> CrossGroupZKP {
+        let g2 = BigUint::from(2u32).modpow(&BigUint::from(6u32), &BigUint::from(67u32));
+        CrossGroupZKP::new(BigUint::from(23u32), BigUint::from(4u32), BigUint::from(67u32), g2, BigUint::from(11u32)).unwrap()
+    }
+
+    #[test]
+    fn test_toy_example() {
+        let zkp = toy();
+        let (x, k, c) = (BigUint::from(6u32), BigUint::from(7u32), BigUint::from(4u32));
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        assert_eq!(y1, BigUint::from(2u32));
+        assert_eq!(y2, BigUint::from(64u32).modpow(&x, &BigUint::from(67u32)));
+
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert_eq!(s, BigUint::from(5u32));
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        // y2 for another x fails, even with a valid proof for y1 alone
+        let (_, y2_other) = zkp.register_commitment(&BigUint::from(3u32));
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2_other, &c, &s));
+
+        let s_fake = core::solve(&k, &c, &BigUint::from(5u32), zkp.q()).unwrap();
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake));
+    }
+
+    #[test]
+    fn test_random_runs() {
+        let zkp = toy();
+
+        for _ in 0..20 {
+            let x = ZKP::generate_rand_below(zkp.q());
+            let k = ZKP::generate_rand_below(zkp.q());
+            let c = ZKP::generate_rand_below(zkp.q());
+            let (y1, y2) = zkp.register_commitment(&x);
+            let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+
+            // c = 0 is rejected, anyone could answer it without x
+            assert_eq!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s), c != BigUint::from(0u32));
+        }
+    }
+
+    #[test]
+    fn test_new_rejects_mismatched_groups() {
+        let (p1, g1, q) = (BigUint::from(23u32), BigUint::from(4u32), BigUint::from(11u32));
+
+        // 11 does not divide 59 - 1
+        assert!(CrossGroupZKP::new(p1.clone(), g1.clone(), BigUint::from(59u32), BigUint::from(4u32), q.clone()).is_err());
+        // 2 has order 66 mod 67, not 11
+        assert!(CrossGroupZKP::new(p1.clone(), g1.clone(), BigUint::from(67u32), BigUint::from(2u32), q.clone()).is_err());
+        assert!(CrossGroupZKP::new(p1, g1, BigUint::from(66u32), BigUint::from(64u32), q).is_err());
+    }
+
+    #[test]
+    fn test_new_rejects_composite_q() {
+        // 5 has order 22 mod 23, every other check passes for q = 22
+        let (p, g) = (BigUint::from(23u32), BigUint::from(5u32));
+        let result = CrossGroupZKP::new(p.clone(), g.clone(), p, g, BigUint::from(22u32));
+        assert_eq!(result, Err(ZKPError::InvalidParameter("q is not prime".to_string())));
+    }
+}
@@ -0,0 +1,144 @@
+//! Chaum-Pedersen over the Ristretto group
+//!
+//! Same protocol as the `num-bigint` version with alpha and beta as curve points:
+//! exponentiation becomes scalar multiplication and the group operation is point
+//! addition, so the verifier checks
+//! cond1: r1 = s*alpha + c*y1
+//! cond2: r2 = s*beta + c*y2
+
+use curve25519_dalek::constants::RISTRETTO_BASEPOINT_POINT;
+use curve25519_dalek::ristretto::RistrettoPoint;
+use curve25519_dalek::scalar::Scalar;
+use sha2::Sha512;
+
+pub struct ZKP {
+    alpha: RistrettoPoint,
+    beta: RistrettoPoint,
+}
+
+impl ZKP {
+    pub fn new(alpha: RistrettoPoint, beta: RistrettoPoint) -> ZKP {
+        ZKP{alpha, beta}
+    }
+
+    /// alpha is the Ristretto base point and beta is hashed from a fixed
+    /// domain string, so nobody knows log_alpha(beta)
+    pub fn default_ristretto() -> ZKP {
+        let alpha = RISTRETTO_BASEPOINT_POINT;
+        let beta = RistrettoPoint::hash_from_bytes::<Sha512>(b"zkp-chaum-pedersen beta");
+        ZKP{alpha, beta}
+    }
+
+    pub fn alpha(&self) -> &RistrettoPoint {
+        &self.alpha
+    }
+
+    pub fn beta(&self) -> &RistrettoPoint {
+        &self.beta
+    }
+
+    /// output = n*point
+    pub fn exponentiate(point: &RistrettoPoint, n: &Scalar) -> RistrettoPoint {
+        n * point
+    }
+
+    /// output = (y1, y2) = (x*alpha, x*beta)
+    pub fn register_commitment(&self, x: &Scalar) -> (RistrettoPoint, RistrettoPoint) {
+        (x * self.alpha, x * self.beta)
+    }
+
+    /// output = s = k - c*x mod l
+    pub fn solve(&self, k: &Scalar, c: &Scalar, x: &Scalar) -> Scalar {
+        k - c * x
+    }
+
+    /// output = (r1, r2, s) with r1 = k*alpha, r2 = k*beta, s = k - c*x
+    pub fn prove(&self, x: &Scalar, k: &Scalar, c: &Scalar) -> (RistrettoPoint, RistrettoPoint, Scalar) {
+        (k * self.alpha, k * self.beta, self.solve(k, c, x))
+    }
+
+    /// cond1: r1 = s*alpha + c*y1
+    /// cond2: r2 = s*beta + c*y2
+    ///
+    /// c = 0 is rejected, it reduces the check to r1 = s*alpha, which anyone can
+    /// satisfy without x. Scalars are always reduced, so there is no range to check
+    pub fn verify(&self, r1: &RistrettoPoint, r2: &RistrettoPoint, y1: &RistrettoPoint, y2: &RistrettoPoint, c: &Scalar, s: &Scalar) -> bool {
+        if *c == Scalar::ZERO {
+            return false;
+        }
+
+        let cond1 = *r1 == s * self.alpha + c * y1;
+        let cond2 = *r2 == s * self.beta + c * y2;
+        cond1 && cond2
+    }
+
+    pub fn generate_rand_scalar() -> Scalar {
+        let mut rng = rand::thread_rng();
+
+        Scalar::random(&mut rng)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_toy_example() {
+        let zkp = ZKP::default_ristretto();
+
+        let x = Scalar::from(6u32);
+        let k = Scalar::from(7u32);
+
+        let c = Scalar::from(4u32);
+
+        let y1 = ZKP::exponentiate(&zkp.alpha, &x);
+        let y2 = ZKP::exponentiate(&zkp.beta, &x);
+
+        let r1 = ZKP::exponentiate(&zkp.alpha, &k);
+        let r2 = ZKP::exponentiate(&zkp.beta, &k);
+
+        // 7 - 4*6 = -17
+        let s = zkp.solve(&k, &c, &x);
+        assert_eq!(s, -Scalar::from(17u32));
+
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
+        assert!(result);
+
+        let x_fake = Scalar::from(7u32);
+        let s_fake = zkp.solve(&k, &c, &x_fake);
+
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake);
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_zero_challenge_is_rejected() {
+        let zkp = ZKP::default_ristretto();
+        let (y1, y2) = zkp.register_commitment(&Scalar::from(6u32));
+
+        // with c = 0, r1 = s*alpha and r2 = s*beta pass for any y1, y2
+        let s = Scalar::from(3u32);
+        let (r1, r2) = zkp.register_commitment(&s);
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &Scalar::ZERO, &s));
+    }
+
+    #[test]
+    fn test_random_scalars() {
+        let zkp = ZKP::default_ristretto();
+
+        let x = ZKP::generate_rand_scalar();
+        let k = ZKP::generate_rand_scalar();
+        let c = ZKP::generate_rand_scalar();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c);
+
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
+        assert!(result);
+
+        // swapping the generators breaks both conditions
+        let swapped = ZKP::new(zkp.beta, zkp.alpha);
+        assert!(!swapped.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+}
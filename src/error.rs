@@ -0,0 +1,107 @@
+use alloc::string::{String, ToString};
+use core::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ZKPError {
+    /// Group parameters or protocol values that are out of range or inconsistent
+    InvalidParameter(String),
+    /// Group parameters that are valid but too small to be secure
+    WeakParameters(String),
+    /// Group parameters that do not belong together, e.g. p of one group and q
+    /// of another
+    InconsistentParameters(String),
+    /// Input that could not be parsed, e.g. malformed hex
+    Decode(String),
+    /// No registration for the given user name
+    UserNotFound(String),
+    /// The user name is already taken by another registration
+    UserAlreadyExists(String),
+    /// No pending challenge for the given auth id
+    ChallengeNotFound(String),
+    /// The challenge for the given auth id is older than the allowed TTL
+    ChallengeExpired(String),
+    /// The solution to the challenge does not verify
+    VerificationFailed,
+    /// The nonce k was already used for another proof
+    NonceReused,
+    /// A session token that is malformed, forged or expired
+    InvalidSession(String),
+    /// Too many failed verifications for the given user
+    RateLimited(String),
+    /// Connection or server failure
+    Transport(String),
+    /// The named operation got no response within the client timeout
+    Timeout(String),
+    /// Reading or writing persisted state failed
+    Storage(String),
+}
+
+impl fmt::Display for ZKPError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ZKPError::InvalidParameter(msg) => write!(f, "invalid parameter: {}", msg),
+            ZKPError::WeakParameters(msg) => write!(f, "weak parameters: {}", msg),
+            ZKPError::InconsistentParameters(msg) => write!(f, "inconsistent parameters: {}", msg),
+            ZKPError::Decode(msg) => write!(f, "decode error: {}", msg),
+            ZKPError::UserNotFound(user) => write!(f, "user {} is not registered", user),
+            ZKPError::UserAlreadyExists(user) => write!(f, "user {} is already registered", user),
+            ZKPError::ChallengeNotFound(auth_id) => write!(f, "auth id {} not found", auth_id),
+            ZKPError::ChallengeExpired(auth_id) => write!(f, "challenge for auth id {} expired", auth_id),
+            ZKPError::VerificationFailed => write!(f, "verification failed"),
+            ZKPError::NonceReused => write!(f, "nonce k was already used"),
+            ZKPError::InvalidSession(reason) => write!(f, "invalid session: {}", reason),
+            ZKPError::RateLimited(user) => write!(f, "too many failed attempts for user {}", user),
+            ZKPError::Transport(msg) => write!(f, "transport error: {}", msg),
+            ZKPError::Timeout(operation) => write!(f, "timeout: {}", operation),
+            ZKPError::Storage(msg) => write!(f, "storage error: {}", msg),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ZKPError {}
+
+#[cfg(feature = "std")]
+impl From<hex::FromHexError> for ZKPError {
+    fn from(err: hex::FromHexError) -> Self {
+        ZKPError::Decode(err.to_string())
+    }
+}
+
+impl From<num_bigint::ParseBigIntError> for ZKPError {
+    fn from(err: num_bigint::ParseBigIntError) -> Self {
+        ZKPError::Decode(err.to_string())
+    }
+}
+
+impl ZKPError {
+    /// Stable identifier of the variant, sent to gRPC clients in the status metadata
+    pub fn code(&self) -> &'static str {
+        match self {
+            ZKPError::InvalidParameter(_) => "invalid-parameter",
+            ZKPError::WeakParameters(_) => "weak-parameters",
+            ZKPError::InconsistentParameters(_) => "inconsistent-parameters",
+            ZKPError::Decode(_) => "decode",
+            ZKPError::UserNotFound(_) => "user-not-found",
+            ZKPError::UserAlreadyExists(_) => "user-already-exists",
+            ZKPError::ChallengeNotFound(_) => "challenge-not-found",
+            ZKPError::ChallengeExpired(_) => "challenge-expired",
+            ZKPError::VerificationFailed => "verification-failed",
+            ZKPError::NonceReused => "nonce-reused",
+            ZKPError::InvalidSession(_) => "invalid-session",
+            ZKPError::RateLimited(_) => "rate-limited",
+            ZKPError::Transport(_) => "transport",
+            ZKPError::Timeout(_) => "timeout",
+            ZKPError::Storage(_) => "storage",
+        }
+    }
+
+    /// Whether starting a new attempt with the same credentials can succeed,
+    /// e.g. after an expired challenge, as opposed to a wrong proof
+    pub fn is_retryable(&self) -> bool {
+        matches!(
+            self,
+            ZKPError::ChallengeNotFound(_) | ZKPError::ChallengeExpired(_) | ZKPError::RateLimited(_) | ZKPError::Transport(_) | ZKPError::Timeout(_) | ZKPError::Storage(_)
+        )
+    }
+}
@@ -1,6 +1,116 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
+
+#[cfg(feature = "std")]
+pub mod zkp_auth {
+    include!("./zkp_auth.rs");
+
+    /// Encoded descriptors of proto/zkp_auth.proto for server reflection
+    pub const FILE_DESCRIPTOR_SET: &[u8] = include_bytes!(concat!(env!("OUT_DIR"), "/zkp_auth_descriptor.bin"));
+}
+
+#[cfg(feature = "std")]
+pub mod accelerator;
+#[cfg(feature = "std")]
+pub mod auth_client;
+#[cfg(feature = "std")]
+pub mod builder;
+#[cfg(feature = "std")]
+pub mod config;
+#[cfg(feature = "std")]
+pub mod cross_group;
+pub mod core;
+#[cfg(feature = "ec")]
+pub mod ec;
+pub mod error;
+#[cfg(feature = "std")]
+pub mod metrics;
+#[cfg(feature = "std")]
+pub mod params;
+#[cfg(feature = "std")]
+pub mod proof;
+#[cfg(feature = "std")]
+pub mod prover;
+#[cfg(feature = "std")]
+pub mod roles;
+#[cfg(feature = "std")]
+pub mod secret;
+#[cfg(feature = "std")]
+pub mod service;
+#[cfg(feature = "std")]
+pub mod session;
+#[cfg(feature = "std")]
+pub mod store;
+#[cfg(test)]
+mod test_support;
+#[cfg(feature = "std")]
+pub mod test_vectors;
+#[cfg(feature = "std")]
+pub mod transcript;
+#[cfg(feature = "std")]
+pub mod verifier;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+#[cfg(feature = "std")]
+use std::fmt;
+
+#[cfg(feature = "std")]
 use num_bigint::{BigUint, RandBigInt};
-use rand;
+#[cfg(feature = "std")]
+use hmac::{Hmac, Mac};
+#[cfg(feature = "std")]
+use once_cell::sync::Lazy;
+#[cfg(feature = "std")]
+use rand::RngCore;
+#[cfg(feature = "std")]
+use sha2::{Digest, Sha256, Sha512};
+#[cfg(feature = "std")]
+use subtle::ConstantTimeEq;
 
+#[cfg(feature = "std")]
+pub use accelerator::Accelerator;
+#[cfg(feature = "std")]
+pub use builder::ZKPBuilder;
+#[cfg(feature = "std")]
+pub use cross_group::CrossGroupZKP;
+pub use error::ZKPError;
+#[cfg(feature = "std")]
+pub use metrics::{Metrics, MetricsSnapshot};
+#[cfg(feature = "std")]
+pub use params::Params;
+#[cfg(feature = "std")]
+pub use proof::{Commitment, Proof, Transcript};
+#[cfg(feature = "std")]
+pub use prover::{ProofSession, Prover};
+#[cfg(feature = "std")]
+pub use roles::{ProverRole, VerifierRole, ZKPProver};
+#[cfg(feature = "std")]
+pub use secret::Secret;
+#[cfg(feature = "std")]
+pub use session::Session;
+#[cfg(feature = "std")]
+pub use transcript::FiatShamirTranscript;
+#[cfg(feature = "std")]
+pub use verifier::Verifier;
+
+/// Which of the two conditions of `ZKP::verify_detailed` failed
+/// cond1: r1 = alpha^s * y1^c
+/// cond2: r2 = beta^s * y2^c
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VerifyOutcome {
+    Ok,
+    Cond1Failed,
+    Cond2Failed,
+    BothFailed,
+}
+
+/// Cloning copies p, q, alpha and beta, a few hundred bytes for the RFC 5114
+/// groups and no modpow. Wrap it in an `Arc` to share one across many tasks,
+/// as `AuthImpl` does
+#[cfg(feature = "std")]
+#[derive(Clone, PartialEq, Eq)]
 pub struct ZKP {
     p: BigUint,
     q: BigUint,
@@ -8,47 +118,871 @@ pub struct ZKP {
     beta: BigUint,
 }
 
+/// One of the toy, 1024-bit and 2048-bit groups
+#[cfg(feature = "arbitrary")]
+impl<'a> arbitrary::Arbitrary<'a> for ZKP {
+    fn arbitrary(u: &mut arbitrary::Unstructured<'a>) -> arbitrary::Result<Self> {
+        Ok(match u.choose_index(3)? {
+            0 => ZKP::toy(),
+            1 => ZKP::default_1024(),
+            _ => ZKP::default_2048(),
+        })
+    }
+}
+
+/// `ZKP::default_1024`, parsed on first use
+#[cfg(feature = "std")]
+static RFC5114_1024: Lazy<ZKP> = Lazy::new(|| ZKP::rfc5114_1024().expect("RFC 5114 constants are valid hex"));
+
+#[cfg(feature = "std")]
 impl ZKP {
+    /// Builds a `ZKP` after checking that:
+    /// - p is probably prime
+    /// - q < p and q divides p - 1
+    /// - alpha and beta have order q mod p, see `validate`
+    pub fn new(p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint) -> Result<ZKP, ZKPError> {
+        if !is_probably_prime(&p, 32) {
+            return Err(ZKPError::InvalidParameter("p is not prime".to_string()));
+        }
+
+        let zkp = ZKP{p, q, alpha, beta};
+        zkp.validate()?;
+        Ok(zkp)
+    }
+
+    /// Smallest p and q accepted by `new_secure`
+    ///
+    /// 2048/224 bits is the smallest finite field size NIST SP 800-57 Part 1
+    /// (Table 2) rates at 112 bits of security, acceptable beyond 2030 only
+    /// with at least 3072/256 bits
+    pub const MIN_SECURE_P_BITS: u64 = 2048;
+    pub const MIN_SECURE_Q_BITS: u64 = 224;
+
+    /// Same checks as `new`, and `WeakParameters` if p has fewer than
+    /// `MIN_SECURE_P_BITS` bits or q fewer than `MIN_SECURE_Q_BITS` bits.
+    ///
+    /// The discrete log in a 1024-bit p is within reach of well funded attackers,
+    /// so this rejects `default_1024` and all toy groups. Use it for parameters
+    /// that come from configuration, `new` is kept for tests
+    pub fn new_secure(p: BigUint, q: BigUint, alpha: BigUint, beta: BigUint) -> Result<ZKP, ZKPError> {
+        if p.bits() < ZKP::MIN_SECURE_P_BITS {
+            return Err(ZKPError::WeakParameters(format!("p has {} bits, at least {} required", p.bits(), ZKP::MIN_SECURE_P_BITS)));
+        }
+        if q.bits() < ZKP::MIN_SECURE_Q_BITS {
+            return Err(ZKPError::WeakParameters(format!("q has {} bits, at least {} required", q.bits(), ZKP::MIN_SECURE_Q_BITS)));
+        }
+
+        ZKP::new(p, q, alpha, beta)
+    }
+
+    /// Checks that q > 1 fits p, `InconsistentParameters` unless q < p and q
+    /// divides p - 1, e.g. for p and q taken from different groups, where
+    /// `solve` and `verify` would silently compute garbage
+    ///
+    /// Then that alpha and beta generate the order q subgroup, for g in alpha, beta:
+    /// - g != 1 and g != p - 1, which have order 1 and 2
+    /// - g^q = 1 mod p
+    /// - g^(q/f) != 1 mod p for every prime factor f of q, so the order is not a
+    ///   proper divisor of q. Nothing to check for prime q
+    ///
+    /// Composite q is only accepted when trial division below 2^16 factors it
+    /// completely, a generator of a subgroup of unknown order is rejected
+    pub fn validate(&self) -> Result<(), ZKPError> {
+        let one = BigUint::from(1u32);
+        if self.q <= one {
+            return Err(ZKPError::InvalidParameter("q must be greater than 1".to_string()));
+        }
+        if self.q >= self.p {
+            return Err(ZKPError::InconsistentParameters("q is not below p".to_string()));
+        }
+        let p_minus_one = &self.p - &one;
+        if &p_minus_one % &self.q != BigUint::from(0u32) {
+            return Err(ZKPError::InconsistentParameters("q does not divide p - 1".to_string()));
+        }
+
+        let factors = match is_probably_prime(&self.q, 32) {
+            true => Vec::new(),
+            false => prime_factors(&self.q).ok_or_else(|| ZKPError::InvalidParameter("q is composite and could not be factored".to_string()))?,
+        };
+
+        for (name, g) in [("alpha", &self.alpha), ("beta", &self.beta)] {
+            if *g == one || *g == p_minus_one || g.modpow(&self.q, &self.p) != one {
+                return Err(ZKPError::InvalidParameter(format!("{} does not have order q", name)));
+            }
+            if factors.iter().any(|f| g.modpow(&(&self.q / f), &self.p) == one) {
+                return Err(ZKPError::InvalidParameter(format!("{} has an order below q", name)));
+            }
+        }
+        Ok(())
+    }
+
+    /// cond: y^q = 1 mod p, i.e. y lies in the order q subgroup generated by
+    /// alpha and beta
+    ///
+    /// A public key y1, y2 outside the subgroup has a component of small order,
+    /// which leaks x mod that order to anyone who checks proofs against it
+    pub fn is_subgroup_element(&self, y: &BigUint) -> bool {
+        y.modpow(&self.q, &self.p) == BigUint::from(1u32)
+    }
+
+    /// Same checks as `new` on big-endian hex encoded parameters, `Decode` for
+    /// malformed hex
+    pub fn from_hex(p: &str, q: &str, alpha: &str, beta: &str) -> Result<ZKP, ZKPError> {
+        ZKP::new(biguint_from_hex(p)?, biguint_from_hex(q)?, biguint_from_hex(alpha)?, biguint_from_hex(beta)?)
+    }
+
+    /// 1024-bit MODP group with 160-bit prime order subgroup
+    /// https://datatracker.ietf.org/doc/html/rfc5114#section-2.1
+    ///
+    /// RFC 5114 only defines alpha, beta = alpha^2 mod p is used as the second
+    /// generator so every party derives the same group
+    ///
+    /// The hex is parsed once on first use, later calls clone the cached group
+    pub fn default_1024() -> ZKP {
+        RFC5114_1024.clone()
+    }
+
+    fn rfc5114_1024() -> Result<ZKP, ZKPError> {
+        let p = biguint_from_hex("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371")?;
+        let q = biguint_from_hex("F518AA8781A8DF278ABA4E7D64B7CB9D49462353")?;
+        let alpha = biguint_from_hex("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5")?;
+        let beta = alpha.modpow(&BigUint::from(2u32), &p);
+
+        Ok(ZKP{p, q, alpha, beta})
+    }
+
+    /// 2048-bit MODP group with 256-bit prime order subgroup
+    /// https://datatracker.ietf.org/doc/html/rfc5114#section-2.3
+    ///
+    /// beta = alpha^2 mod p as in `default_1024`
+    pub fn default_2048() -> ZKP {
+        ZKP::rfc5114_2048().expect("RFC 5114 constants are valid hex")
+    }
+
+    fn rfc5114_2048() -> Result<ZKP, ZKPError> {
+        let p = biguint_from_hex("87A8E61DB4B6663CFFBBD19C651959998CEEF608660DD0F25D2CEED4435E3B00E00DF8F1D61957D4FAF7DF4561B2AA3016C3D91134096FAA3BF4296D830E9A7C209E0C6497517ABD5A8A9D306BCF67ED91F9E6725B4758C022E0B1EF4275BF7B6C5BFC11D45F9088B941F54EB1E59BB8BC39A0BF12307F5C4FDB70C581B23F76B63ACAE1CAA6B7902D52526735488A0EF13C6D9A51BFA4AB3AD8347796524D8EF6A167B5A41825D967E144E5140564251CCACB83E6B486F6B3CA3F7971506026C0B857F689962856DED4010ABD0BE621C3A3960A54E710C375F26375D7014103A4B54330C198AF126116D2276E11715F693877FAD7EF09CADB094AE91E1A1597")?;
+        let q = biguint_from_hex("8CF83642A709A097B447997640129DA299B1A47D1EB3750BA308B0FE64F5FBD3")?;
+        let alpha = biguint_from_hex("3FB32C9B73134D0B2E77506660EDBD484CA7B18F21EF205407F4793A1A0BA12510DBC15077BE463FFF4FED4AAC0BB555BE3A6C1B0C6B47B1BC3773BF7E8C6F62901228F8C28CBB18A55AE31341000A650196F931C77A57F2DDF463E5E9EC144B777DE62AAAB8A8628AC376D282D6ED3864E67982428EBC831D14348F6F2F9193B5045AF2767164E1DFC967C1FB3F2E55A4BD1BFFE83B9C80D052B985D182EA0ADB2A3B7313D3FE14C8484B1E052588B9B7D2BBD2DF016199ECD06E1557CD0915B3353BBB64E0EC377FD028370DF92B52C7891428CDC67EB6184B523D1DB246C32F63078490F00EF8D647D148D47954515E2327CFEF98C582664B4C0F6CC41659")?;
+        let beta = alpha.modpow(&BigUint::from(2u32), &p);
+
+        Ok(ZKP{p, q, alpha, beta})
+    }
+
+    /// INSECURE toy group p = 23, q = 11, alpha = 4, beta = 9, only for tests and examples
+    ///
+    /// x can be brute forced from y1 in at most 11 tries, never use it to protect anything
+    pub fn toy() -> ZKP {
+        let p = BigUint::from(23u32);
+        let q = BigUint::from(11u32);
+        let alpha = BigUint::from(4u32);
+        let beta = BigUint::from(9u32);
+
+        ZKP{p, q, alpha, beta}
+    }
+
+    /// New group with a safe prime p = 2q + 1 of `bits` bits and prime q, for
+    /// setups that want their own parameters instead of the RFC 5114 ones
+    ///
+    /// alpha = h^2 mod p for a random h, which has order q unless it is 1, and
+    /// beta comes from `derive_beta` with the domain "zkp-chaum-pedersen". Finding
+    /// a safe prime takes many candidates: a fraction of a second for 256 bits,
+    /// minutes for 2048. `InvalidParameter` for fewer than 8 bits
+    pub fn generate_group<R: RngCore>(bits: usize, rng: &mut R) -> Result<ZKP, ZKPError> {
+        if bits < 8 {
+            return Err(ZKPError::InvalidParameter(format!("groups need at least 8 bits, got {}", bits)));
+        }
+
+        let one = BigUint::from(1u32);
+        let (p, q) = loop {
+            // q with exactly bits - 1 bits and odd, so p = 2q + 1 has exactly bits bits
+            let mut q = rng.gen_biguint(bits as u64 - 1);
+            q.set_bit(bits as u64 - 2, true);
+            q.set_bit(0, true);
+            let p = &q * 2u32 + &one;
+
+            // trial division rules out most candidates before any modpow
+            let has_small_factor = |n: &BigUint| SMALL_PRIMES.iter().any(|&prime| n % prime == BigUint::from(0u32) && *n != BigUint::from(prime));
+            let small_factor = has_small_factor(&q) || has_small_factor(&p);
+            if !small_factor && is_probably_prime(&q, 32) && is_probably_prime(&p, 32) {
+                break (p, q);
+            }
+        };
+
+        let alpha = loop {
+            let h = rng.gen_biguint_range(&BigUint::from(2u32), &(&p - &one));
+            let alpha = h.modpow(&BigUint::from(2u32), &p);
+            if alpha != one {
+                break alpha;
+            }
+        };
+        let beta = ZKP::derive_beta(&alpha, &p, &q, "zkp-chaum-pedersen");
+
+        ZKP::new(p, q, alpha, beta)
+    }
+
+    /// output = beta = alpha^e mod p with e = SHA-256(domain || counter) mod q
+    ///
+    /// Lets every party compute the same second generator from alpha and a domain
+    /// string. The counter is bumped until e is non-zero so beta never collapses to 1
+    pub fn derive_beta(alpha: &BigUint, p: &BigUint, q: &BigUint, domain: &str) -> BigUint {
+        let zero = BigUint::from(0u32);
+        let mut counter = 0u32;
+
+        loop {
+            let mut hasher = Sha256::new();
+            hasher.update(domain.as_bytes());
+            hasher.update(counter.to_be_bytes());
+
+            let e = BigUint::from_bytes_be(&hasher.finalize()) % q;
+            if e != zero {
+                return alpha.modpow(&e, p);
+            }
+            counter += 1;
+        }
+    }
+
+    /// Named groups accepted by `from_named_group`
+    pub const GROUP_NAMES: [&'static str; 2] = ["rfc5114-1024", "rfc5114-2048"];
+
+    /// Known group by name, e.g. from a config file or the command line:
+    /// "rfc5114-1024" is `default_1024`, "rfc5114-2048" is `default_2048`
+    pub fn from_named_group(name: &str) -> Result<ZKP, ZKPError> {
+        match name {
+            "rfc5114-1024" => ZKP::rfc5114_1024(),
+            "rfc5114-2048" => ZKP::rfc5114_2048(),
+            _ => Err(ZKPError::InvalidParameter(format!("unknown group {}, expected one of {}", name, ZKP::GROUP_NAMES.join(", ")))),
+        }
+    }
+
+    /// Copy of p, alpha and beta, enough to compute commitments and responses
+    pub fn params(&self) -> Params {
+        Params::new(self.p.clone(), self.alpha.clone(), self.beta.clone())
+    }
+
+    /// Prime modulus of the group
+    pub fn p(&self) -> &BigUint {
+        &self.p
+    }
+
+    /// Order of the subgroup generated by alpha and beta
+    ///
+    /// ```
+    /// use zkp_chaum_pedersen::ZKP;
+    ///
+    /// let zkp = ZKP::toy();
+    /// let k = ZKP::generate_rand_below(zkp.q());
+    /// assert!(&k < zkp.q());
+    /// ```
+    pub fn q(&self) -> &BigUint {
+        &self.q
+    }
+
+    /// First generator of the order q subgroup
+    pub fn alpha(&self) -> &BigUint {
+        &self.alpha
+    }
+
+    /// Second generator of the order q subgroup
+    pub fn beta(&self) -> &BigUint {
+        &self.beta
+    }
+
     /// output = n^exp mod p
     pub fn exponentiate(n: &BigUint, exponent: &BigUint, modulus: &BigUint) -> BigUint {
-        n.modpow(exponent, modulus)
+        core::exponentiate(n, exponent, modulus)
     }
 
-    /// output = s = k- c*x
-    pub fn solve(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> BigUint {
-        if *k >= c * x {
-            return (k - c*x).modpow(&BigUint::from(1u32), &self.q);
-        }
-        return &self.q - (c*x - k).modpow(&BigUint::from(1u32), &self.q);
+    /// output = s = k - c*x mod q
+    ///
+    /// k, c and x are reduced mod q first, so the output is always in [0, q).
+    /// x >= q is fine, the effective witness is x mod q, see `core::solve`
+    pub fn solve(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> Result<BigUint, ZKPError> {
+        core::solve(k, c, x, &self.q)
+    }
+
+    /// Same as `solve`, named for verifier-side tooling that replays a proof
+    /// with a known k and x to get the s the prover should have sent
+    pub fn expected_s(&self, k: &BigUint, c: &BigUint, x: &BigUint) -> Result<BigUint, ZKPError> {
+        self.solve(k, c, x)
+    }
+
+    /// output = x = (k - s) * c^-1 mod q
+    ///
+    /// Anyone who learns the nonce k of a single proof gets the secret from
+    /// the public c and s, which is why k must stay secret and never be reused.
+    /// Errors if c = 0 mod q, which has no inverse
+    pub fn recover_x(&self, k: &BigUint, c: &BigUint, s: &BigUint) -> Result<BigUint, ZKPError> {
+        let c_inv = (c % &self.q).modinv(&self.q)
+            .ok_or_else(|| ZKPError::InvalidParameter("c has no inverse mod q".to_string()))?;
+        let k_minus_s = (k % &self.q + &self.q - s % &self.q) % &self.q;
+        Ok(k_minus_s * c_inv % &self.q)
+    }
+
+    /// output = (y1, y2) = (alpha^x mod p, beta^x mod p)
+    pub fn register_commitment(&self, x: &BigUint) -> (BigUint, BigUint) {
+        let y1 = ZKP::exponentiate(&self.alpha, x, &self.p);
+        let y2 = ZKP::exponentiate(&self.beta, x, &self.p);
+        (y1, y2)
+    }
+
+    /// output = (r1, r2, s) with r1 = alpha^k mod p, r2 = beta^k mod p, s = k - c*x mod q
+    pub fn prove(&self, x: &BigUint, k: &BigUint, c: &BigUint) -> Result<(BigUint, BigUint, BigUint), ZKPError> {
+        let r1 = ZKP::exponentiate(&self.alpha, k, &self.p);
+        let r2 = ZKP::exponentiate(&self.beta, k, &self.p);
+        let s = self.solve(k, c, x)?;
+        Ok((r1, r2, s))
     }
 
     /// cond1: r1 = alpha^s * y1^c
     /// cond2: r2 = beta^s * y2^c
+    ///
+    /// `BigUint` equality short-circuits on the first differing limb, so the
+    /// running time can leak how close r1/r2 were to the expected values.
+    /// Use `verify_constant_time` when that matters
+    ///
+    /// c outside [1, q) and s outside [0, q) are rejected without doing any modpow, see
+    /// `core::scalars_in_range`
     pub fn verify(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> bool {
-        let cond1 = *r1 == (&self.alpha.modpow(s, &self.p) * y1.modpow(c, &self.p)).modpow(&BigUint::from(1u32), &self.p);
-        let cond2 = *r2 == (&self.beta.modpow(s, &self.p) * y2.modpow(c, &self.p)).modpow(&BigUint::from(1u32), &self.p);
+        let cond1 = core::verify(&self.p, &self.q, &self.alpha, y1, r1, c, s);
+        let cond2 = core::verify(&self.p, &self.q, &self.beta, y2, r2, c, s);
         cond1 && cond2
     }
 
+    /// Starts a proof of knowledge of x with a fresh random nonce k kept inside
+    /// the session: send `r1`/`r2`, then answer the challenge with `respond`
+    pub fn prove_with_auto_nonce<'a>(&'a self, x: &'a BigUint) -> ProofSession<'a> {
+        ProofSession::new(self, x)
+    }
+
+    /// Same checks as `verify`, reporting which condition failed. c or s out
+    /// of range fails both
+    pub fn verify_detailed(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> VerifyOutcome {
+        let cond1 = core::verify(&self.p, &self.q, &self.alpha, y1, r1, c, s);
+        let cond2 = core::verify(&self.p, &self.q, &self.beta, y2, r2, c, s);
+
+        match (cond1, cond2) {
+            (true, true) => VerifyOutcome::Ok,
+            (false, true) => VerifyOutcome::Cond1Failed,
+            (true, false) => VerifyOutcome::Cond2Failed,
+            (false, false) => VerifyOutcome::BothFailed,
+        }
+    }
+
+    /// `verify_detailed` on the values of `transcript`
+    pub fn verify_transcript(&self, transcript: &Transcript) -> VerifyOutcome {
+        let Transcript{y1, y2, r1, r2, c, s} = transcript;
+        self.verify_detailed(r1, r2, y1, y2, c, s)
+    }
+
+    /// Schnorr proof with alpha only
+    /// output = (r1, s) with r1 = alpha^k mod p, s = k - c*x mod q
+    pub fn prove_single(&self, x: &BigUint, k: &BigUint, c: &BigUint) -> Result<(BigUint, BigUint), ZKPError> {
+        let r1 = ZKP::exponentiate(&self.alpha, k, &self.p);
+        let s = self.solve(k, c, x)?;
+        Ok((r1, s))
+    }
+
+    /// Schnorr check with alpha only, y1 = alpha^x mod p
+    /// cond: r1 = alpha^s * y1^c
+    pub fn verify_single(&self, r1: &BigUint, y1: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+        core::verify(&self.p, &self.q, &self.alpha, y1, r1, c, s)
+    }
+
+    /// Same as `verify` but both sides of each condition are encoded to the
+    /// byte width of p and compared in constant time
+    pub fn verify_constant_time(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint, c: &BigUint, s: &BigUint) -> bool {
+        if !self.in_scalar_range(c, s) {
+            return false;
+        }
+
+        let width = self.element_width();
+
+        let expected1 = (&self.alpha.modpow(s, &self.p) * y1.modpow(c, &self.p)) % &self.p;
+        let expected2 = (&self.beta.modpow(s, &self.p) * y2.modpow(c, &self.p)) % &self.p;
+
+        let cond1 = ZKP::encode_fixed(r1, width).ct_eq(&ZKP::encode_fixed(&expected1, width));
+        let cond2 = ZKP::encode_fixed(r2, width).ct_eq(&ZKP::encode_fixed(&expected2, width));
+        (cond1 & cond2).into()
+    }
+
+    fn in_scalar_range(&self, c: &BigUint, s: &BigUint) -> bool {
+        core::scalars_in_range(&self.q, c, s)
+    }
+
+    /// Verifies every `(proof, commitment)` pair, the output lines up index by
+    /// index with the input
+    pub fn verify_batch(&self, proofs: &[(Proof, Commitment)]) -> Vec<bool> {
+        proofs
+            .iter()
+            .map(|(proof, commitment)| self.verify(&proof.r1, &proof.r2, &commitment.y1, &commitment.y2, &proof.c, &proof.s))
+            .collect()
+    }
+
+    /// Non-interactive (Fiat-Shamir) proof of knowledge of x
+    /// c = SHA-256(alpha || beta || y1 || y2 || r1 || r2) mod q
+    /// each value is encoded to the byte width of p before hashing
+    pub fn prove_noninteractive(&self, x: &BigUint, k: &BigUint) -> Result<Proof, ZKPError> {
+        self.prove_noninteractive_with::<Sha256>(x, k)
+    }
+
+    /// Same as `prove_noninteractive` with the hash `D`, e.g. `sha2::Sha512`
+    /// c = D(alpha || beta || y1 || y2 || r1 || r2) mod q
+    ///
+    /// The whole digest is read as a big-endian number and reduced mod q, so a
+    /// digest wider than q only lowers the bias. Proofs only verify with
+    /// `verify_noninteractive_with` for the same `D`
+    pub fn prove_noninteractive_with<D: Digest>(&self, x: &BigUint, k: &BigUint) -> Result<Proof, ZKPError> {
+        let (y1, y2) = self.register_commitment(x);
+        let r1 = ZKP::exponentiate(&self.alpha, k, &self.p);
+        let r2 = ZKP::exponentiate(&self.beta, k, &self.p);
+
+        let c = self.fiat_shamir_challenge::<D>(&y1, &y2, &r1, &r2);
+        let s = self.solve(k, &c, x)?;
+
+        Ok(Proof{r1, r2, c, s})
+    }
+
+    /// `prove_noninteractive` with k = `deterministic_nonce(x, transcript)`, the
+    /// same x and transcript always give the same proof
+    pub fn prove_noninteractive_deterministic(&self, x: &BigUint, transcript: &[u8]) -> Result<Proof, ZKPError> {
+        let k = Secret::new(self.deterministic_nonce(x, transcript));
+        self.prove_noninteractive(x, &k)
+    }
+
+    /// output = k = HMAC-SHA256(key = x, transcript || 0) || HMAC-SHA256(key = x, transcript || 1) mod q
+    ///
+    /// RFC 6979 style nonce meant for reproducible tests and interop vectors.
+    /// Answering two different challenges with the k of one transcript leaks x,
+    /// so only use it where the transcript fixes the challenge, e.g.
+    /// `prove_noninteractive_deterministic`, and prefer random nonces otherwise
+    pub fn deterministic_nonce(&self, x: &BigUint, transcript: &[u8]) -> BigUint {
+        let key = Secret::new(x % &self.q);
+        let key = ZKP::encode_fixed(&key, self.scalar_width());
+        let zero = BigUint::from(0u32);
+
+        // 512 bits reduced mod q keeps the bias negligible, the counter moves
+        // on to the next pair of blocks in the unlikely case of k = 0
+        let mut counter = 0u32;
+        loop {
+            let mut bytes = Vec::with_capacity(64);
+            for _ in 0..2 {
+                let mut mac = Hmac::<Sha256>::new_from_slice(&key).expect("HMAC accepts keys of any length");
+                mac.update(transcript);
+                mac.update(&counter.to_be_bytes());
+                bytes.extend_from_slice(&mac.finalize().into_bytes());
+                counter += 1;
+            }
+
+            let k = BigUint::from_bytes_be(&bytes) % &self.q;
+            if k != zero {
+                return k;
+            }
+        }
+    }
+
+    /// Recomputes c from the commitment and the proof, then runs `verify`
+    pub fn verify_noninteractive(&self, commitment: &Commitment, proof: &Proof) -> bool {
+        self.verify_noninteractive_with::<Sha256>(commitment, proof)
+    }
+
+    /// Same as `verify_noninteractive` for proofs from `prove_noninteractive_with::<D>`
+    pub fn verify_noninteractive_with<D: Digest>(&self, commitment: &Commitment, proof: &Proof) -> bool {
+        let c = self.fiat_shamir_challenge::<D>(&commitment.y1, &commitment.y2, &proof.r1, &proof.r2);
+        if c != proof.c {
+            return false;
+        }
+        self.verify(&proof.r1, &proof.r2, &commitment.y1, &commitment.y2, &c, &proof.s)
+    }
+
+    fn fiat_shamir_challenge<D: Digest>(&self, y1: &BigUint, y2: &BigUint, r1: &BigUint, r2: &BigUint) -> BigUint {
+        let width = self.element_width();
+
+        let mut hasher = D::new();
+        for value in [&self.alpha, &self.beta, y1, y2, r1, r2] {
+            hasher.update(ZKP::encode_fixed(value, width));
+        }
+
+        BigUint::from_bytes_be(&hasher.finalize()) % &self.q
+    }
+
+    /// Bytes needed for an element mod p (y1, y2, r1, r2)
+    pub fn element_width(&self) -> usize {
+        self.p.bits().div_ceil(8) as usize
+    }
+
+    /// Bytes needed for a scalar mod q (c, s)
+    pub fn scalar_width(&self) -> usize {
+        self.q.bits().div_ceil(8) as usize
+    }
+
+    /// Bytes of a fixed-width encoded (r1, r2, c, s)
+    pub fn proof_size(&self) -> usize {
+        2 * self.element_width() + 2 * self.scalar_width()
+    }
+
+    /// output = r1 || r2 || c || s, each big-endian and left-padded to
+    /// `element_width` (r1, r2) or `scalar_width` (c, s), `proof_size` bytes in total
+    ///
+    /// Like `encode_fixed` nothing is truncated, a value wider than its field
+    /// makes the output longer and `decode_proof` rejects it
+    pub fn encode_proof(&self, proof: &Proof) -> Vec<u8> {
+        let (element_width, scalar_width) = (self.element_width(), self.scalar_width());
+
+        let mut bytes = Vec::with_capacity(self.proof_size());
+        bytes.extend(ZKP::encode_fixed(&proof.r1, element_width));
+        bytes.extend(ZKP::encode_fixed(&proof.r2, element_width));
+        bytes.extend(ZKP::encode_fixed(&proof.c, scalar_width));
+        bytes.extend(ZKP::encode_fixed(&proof.s, scalar_width));
+        bytes
+    }
+
+    /// Inverse of `encode_proof`, input that is not exactly `proof_size` bytes is rejected
+    pub fn decode_proof(&self, bytes: &[u8]) -> Result<Proof, ZKPError> {
+        if bytes.len() != self.proof_size() {
+            return Err(ZKPError::Decode(format!("expected a {} byte proof, got {} bytes", self.proof_size(), bytes.len())));
+        }
+
+        let (r1, rest) = bytes.split_at(self.element_width());
+        let (r2, rest) = rest.split_at(self.element_width());
+        let (c, s) = rest.split_at(self.scalar_width());
+
+        Ok(Proof {
+            r1: BigUint::from_bytes_be(r1),
+            r2: BigUint::from_bytes_be(r2),
+            c: BigUint::from_bytes_be(c),
+            s: BigUint::from_bytes_be(s),
+        })
+    }
+
+    /// Big-endian encoding left-padded with zeros to `width_bytes`
+    ///
+    /// `to_bytes_be` drops leading zeros, so equal values can otherwise end up
+    /// with different lengths. Values wider than `width_bytes` keep their full
+    /// length and so never compare equal to a properly sized encoding
+    pub fn encode_fixed(value: &BigUint, width_bytes: usize) -> Vec<u8> {
+        let bytes = value.to_bytes_be();
+        if bytes.len() >= width_bytes {
+            return bytes;
+        }
+
+        let mut padded = vec![0u8; width_bytes - bytes.len()];
+        padded.extend_from_slice(&bytes);
+        padded
+    }
+
+    /// Inverse of `encode_fixed`, shorter input is treated as left-padded and
+    /// input longer than `width_bytes` is rejected
+    pub fn decode_fixed(bytes: &[u8], width_bytes: usize) -> Result<BigUint, ZKPError> {
+        if bytes.len() > width_bytes {
+            return Err(ZKPError::Decode(format!("expected at most {} bytes, got {}", width_bytes, bytes.len())));
+        }
+        Ok(BigUint::from_bytes_be(bytes))
+    }
+
+    /// Big-endian element mod p (y1, y2, r1, r2) received over the wire,
+    /// `InvalidParameter` for values >= p
+    ///
+    /// Input longer than `element_width` is rejected before it is decoded, so
+    /// a peer cannot make us parse and later modpow with megabyte-long numbers
+    pub fn element_from_bytes(&self, bytes: &[u8]) -> Result<BigUint, ZKPError> {
+        if bytes.len() > self.element_width() {
+            return Err(ZKPError::InvalidParameter(format!("element has {} bytes, at most {} expected", bytes.len(), self.element_width())));
+        }
+        let element = BigUint::from_bytes_be(bytes);
+        if element >= self.p {
+            return Err(ZKPError::InvalidParameter("element is not below p".to_string()));
+        }
+        Ok(element)
+    }
+
+    /// Big-endian scalar mod q (c, s) received over the wire, `InvalidParameter`
+    /// for input longer than `scalar_width`
+    ///
+    /// Values >= q that fit the width are returned as is, `verify` rejects them
+    pub fn scalar_from_bytes(&self, bytes: &[u8]) -> Result<BigUint, ZKPError> {
+        if bytes.len() > self.scalar_width() {
+            return Err(ZKPError::InvalidParameter(format!("scalar has {} bytes, at most {} expected", bytes.len(), self.scalar_width())));
+        }
+        Ok(BigUint::from_bytes_be(bytes))
+    }
+
     pub fn generate_rand_below(bound: &BigUint) -> BigUint {
         let mut rng = rand::thread_rng();
 
+        ZKP::generate_rand_below_with(&mut rng, bound)
+    }
+
+    /// Same as `generate_rand_below` with a caller supplied RNG, e.g. a seeded
+    /// one for reproducible tests
+    pub fn generate_rand_below_with<R: RngCore>(rng: &mut R, bound: &BigUint) -> BigUint {
         rng.gen_biguint_below(bound)
     }
+
+    /// Challenge c in [1, q), zero is redrawn since `verify` rejects it
+    pub fn generate_challenge(&self) -> BigUint {
+        let mut rng = rand::thread_rng();
+
+        self.generate_challenge_with(&mut rng)
+    }
+
+    /// Same as `generate_challenge` with a caller supplied RNG
+    pub fn generate_challenge_with<R: RngCore>(&self, rng: &mut R) -> BigUint {
+        let zero = BigUint::from(0u32);
+        loop {
+            let c = rng.gen_biguint_below(&self.q);
+            if c != zero {
+                return c;
+            }
+        }
+    }
+
+    /// Scalar in [1, q) taken from fuzz input, e.g. a c or s that passes
+    /// `core::scalars_in_range`. 8 bytes more than `scalar_width` are reduced so
+    /// every value is about equally likely, missing input counts as zeros
+    #[cfg(feature = "arbitrary")]
+    pub fn arbitrary_scalar(&self, u: &mut arbitrary::Unstructured) -> arbitrary::Result<BigUint> {
+        let mut bytes = vec![0u8; self.scalar_width() + 8];
+        u.fill_buffer(&mut bytes)?;
+        Ok(BigUint::from_bytes_be(&bytes) % (&self.q - 1u32) + 1u32)
+    }
+
+    /// output = alpha^r mod p with r random in [1, q), e.g. to blind a value or
+    /// as a test input
+    ///
+    /// Always an element of the order q subgroup and never 1, r = 0 is redrawn
+    pub fn random_element<R: RngCore>(&self, rng: &mut R) -> BigUint {
+        let zero = BigUint::from(0u32);
+        loop {
+            let r = Secret::new(rng.gen_biguint_below(&self.q));
+            if *r != zero {
+                return ZKP::exponentiate(&self.alpha, &r, &self.p);
+            }
+        }
+    }
+
+    /// Challenge c in [1, q) bound to the commitments of this exchange, with a
+    /// fresh 32 byte random salt, see `challenge_bound_with_salt`
+    ///
+    /// `generate_challenge` is enough for the soundness of the interactive
+    /// protocol. Use this one when the challenge should only be valid for the
+    /// r1/r2 it was issued for, e.g. so a relay cannot reuse a challenge obtained
+    /// for its own commitments to answer for someone else's
+    pub fn challenge_bound(&self, r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint) -> BigUint {
+        let mut salt = [0u8; 32];
+        rand::thread_rng().fill_bytes(&mut salt);
+
+        self.challenge_bound_with_salt(&salt, r1, r2, y1, y2)
+    }
+
+    /// output = c = SHA-256(salt || alpha || beta || y1 || y2 || r1 || r2) mod (q - 1) + 1
+    ///
+    /// Elements are encoded to the byte width of p as in `prove_noninteractive`.
+    /// The salt keeps the challenge unpredictable to the prover, reusing a salt
+    /// for the same commitments gives the same challenge
+    pub fn challenge_bound_with_salt(&self, salt: &[u8], r1: &BigUint, r2: &BigUint, y1: &BigUint, y2: &BigUint) -> BigUint {
+        let width = self.element_width();
+
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        for value in [&self.alpha, &self.beta, y1, y2, r1, r2] {
+            hasher.update(ZKP::encode_fixed(value, width));
+        }
+
+        let one = BigUint::from(1u32);
+        BigUint::from_bytes_be(&hasher.finalize()) % (&self.q - &one) + one
+    }
+
+    /// output = c = HMAC-SHA512(key, len(auth_id) || auth_id || salt || timestamp) mod (q - 1) + 1,
+    /// the length of auth_id in bytes and the timestamp as 8 big-endian bytes
+    ///
+    /// `salt` is the prover's, so two provers never get the same c for the same
+    /// auth id and timestamp, see `store::UserRegistration::salt`
+    ///
+    /// Alternative to a random c for a server that wants challenges bound to a
+    /// secret key and the time they were issued: anyone holding `key` recomputes
+    /// c from the auth id and timestamp alone, e.g. another replica. The price is
+    /// that c is only as unpredictable as the key. A random c needs no secret at
+    /// all, while whoever learns the key can compute challenges ahead of time and
+    /// answer them without x, by picking s and setting r1 = alpha^s * y1^c.
+    /// The 512-bit MAC leaves a bias towards small c of about 2^-(512 - bits of q),
+    /// negligible for every q this crate supports
+    pub fn keyed_challenge(&self, key: &[u8], auth_id: &str, salt: &[u8], timestamp: u64) -> BigUint {
+        let mut mac = Hmac::<Sha512>::new_from_slice(key).expect("HMAC accepts keys of any length");
+        mac.update(&(auth_id.len() as u64).to_be_bytes());
+        mac.update(auth_id.as_bytes());
+        mac.update(salt);
+        mac.update(&timestamp.to_be_bytes());
+
+        let one = BigUint::from(1u32);
+        BigUint::from_bytes_be(&mac.finalize().into_bytes()) % (&self.q - &one) + one
+    }
+
+    /// Whether `other` has the same p, q, alpha and beta, so proofs from one
+    /// verify in the other
+    pub fn same_group(&self, other: &ZKP) -> bool {
+        self == other
+    }
+
+    /// output = SHA-256("zkp-chaum-pedersen/group" || p || q || alpha || beta),
+    /// each left-padded to `element_width`
+    ///
+    /// Compact identifier for client and server to compare at handshake: equal
+    /// fingerprints mean `same_group`, up to SHA-256 collisions
+    pub fn group_fingerprint(&self) -> [u8; 32] {
+        let width = self.element_width();
+
+        let mut hasher = Sha256::new();
+        hasher.update(b"zkp-chaum-pedersen/group");
+        for value in [&self.p, &self.q, &self.alpha, &self.beta] {
+            hasher.update(ZKP::encode_fixed(value, width));
+        }
+        hasher.finalize().into()
+    }
 }
 
-#[cfg(test)]
+/// Prints p, q, alpha and beta as the first hex digits and the bit length.
+/// Fields are listed explicitly so anything added to `ZKP` later is not printed
+#[cfg(feature = "std")]
+impl fmt::Debug for ZKP {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ZKP")
+            .field("p", &ShortHex(&self.p))
+            .field("q", &ShortHex(&self.q))
+            .field("alpha", &ShortHex(&self.alpha))
+            .field("beta", &ShortHex(&self.beta))
+            .finish_non_exhaustive()
+    }
+}
+
+/// e.g. "1024-bit p, 160-bit q"
+#[cfg(feature = "std")]
+impl fmt::Display for ZKP {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-bit p, {}-bit q", self.p.bits(), self.q.bits())
+    }
+}
+
+/// output = "b10b8f96..(1024 bits)"
+#[cfg(feature = "std")]
+struct ShortHex<'a>(&'a BigUint);
+
+#[cfg(feature = "std")]
+impl fmt::Debug for ShortHex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        const DIGITS: usize = 8;
+
+        let hex = self.0.to_str_radix(16);
+        if hex.len() > DIGITS {
+            write!(f, "{}..({} bits)", &hex[..DIGITS], self.0.bits())
+        } else {
+            write!(f, "{}({} bits)", hex, self.0.bits())
+        }
+    }
+}
+
+/// Self-test of one whole login in-process: y1, y2 from `x`, r1, r2 from a
+/// fresh random k, a random challenge c, the response s and `ZKP::verify`.
+/// Checks the parameters and the wiring before any gRPC is involved
+#[cfg(feature = "std")]
+pub fn authenticate_local(zkp: &ZKP, x: &BigUint) -> bool {
+    let (y1, y2) = zkp.register_commitment(x);
+    let k = Secret::new(ZKP::generate_rand_below(zkp.q()));
+    let c = zkp.generate_challenge();
+
+    let Ok((r1, r2, s)) = zkp.prove(x, &k, &c) else { return false };
+    zkp.verify(&r1, &r2, &y1, &y2, &c, &s)
+}
+
+/// Big-endian hex as in `ZKP::from_hex`
+#[cfg(feature = "std")]
+fn biguint_from_hex(value: &str) -> Result<BigUint, ZKPError> {
+    Ok(BigUint::from_bytes_be(&hex::decode(value)?))
+}
+
+/// Odd primes below 200, for trial division in `ZKP::generate_group`
+#[cfg(feature = "std")]
+const SMALL_PRIMES: [u32; 45] = [
+    3, 5, 7, 11, 13, 17, 19, 23, 29, 31, 37, 41, 43, 47, 53, 59, 61, 67, 71, 73, 79, 83, 89, 97, 101, 103, 107, 109, 113, 127, 131, 137, 139, 149, 151, 157,
+    163, 167, 173, 179, 181, 191, 193, 197, 199,
+];
+
+/// Distinct prime factors of `n` > 1, `None` if a cofactor is left that is
+/// neither 1 nor prime after trial division by every d < 2^16
+#[cfg(feature = "std")]
+fn prime_factors(n: &BigUint) -> Option<Vec<BigUint>> {
+    let zero = BigUint::from(0u32);
+    let one = BigUint::from(1u32);
+    let mut n = n.clone();
+    let mut factors = Vec::new();
+
+    for d in 2u32..1 << 16 {
+        if n == one || BigUint::from(d) * d > n {
+            break;
+        }
+        if &n % d == zero {
+            factors.push(BigUint::from(d));
+            while &n % d == zero {
+                n /= d;
+            }
+        }
+    }
+
+    if n != one {
+        if !is_probably_prime(&n, 32) {
+            return None;
+        }
+        factors.push(n);
+    }
+    Some(factors)
+}
+
+/// Miller-Rabin test with `rounds` random bases
+#[cfg(feature = "std")]
+fn is_probably_prime(n: &BigUint, rounds: usize) -> bool {
+    let one = BigUint::from(1u32);
+    let two = BigUint::from(2u32);
+    let three = BigUint::from(3u32);
+
+    if *n < two {
+        return false;
+    }
+    if *n == two || *n == three {
+        return true;
+    }
+    if !n.bit(0) {
+        return false;
+    }
+
+    // n - 1 = d * 2^r with d odd
+    let n_minus_one = n - &one;
+    let r = n_minus_one.trailing_zeros().unwrap_or(0);
+    let d = &n_minus_one >> r;
+
+    let mut rng = rand::thread_rng();
+    'witness: for _ in 0..rounds {
+        let a = rng.gen_biguint_range(&two, &n_minus_one);
+        let mut x = a.modpow(&d, n);
+        if x == one || x == n_minus_one {
+            continue;
+        }
+        for _ in 1..r {
+            x = x.modpow(&two, n);
+            if x == n_minus_one {
+                continue 'witness;
+            }
+        }
+        return false;
+    }
+    true
+}
+
+#[cfg(all(test, feature = "std"))]
 mod test {
     use super::*;
 
     #[test]
     fn test_toy_example() {
-        let alpha = BigUint::from(4u32);
-        let beta = BigUint::from(9u32);
-        let p = BigUint::from(23u32);
-        let q = BigUint::from(11u32);
-
-        let zkp = ZKP{p, q, alpha, beta};
+        let zkp = ZKP::toy();
 
         let x = BigUint::from(6u32);
         let k = BigUint::from(7u32);
@@ -65,14 +999,14 @@ mod test {
         assert_eq!(r1, BigUint::from(8u32));
         assert_eq!(r2, BigUint::from(4u32));
 
-        let s = zkp.solve(&k, &c, &x);
+        let s = zkp.solve(&k, &c, &x).unwrap();
         assert_eq!(s, BigUint::from(5u32));
 
         let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
         assert!(result);
 
         let x_fake = BigUint::from(7u32);
-        let s_fake = zkp.solve(&k, &c, &x_fake);
+        let s_fake = zkp.solve(&k, &c, &x_fake).unwrap();
 
         let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake);
         assert!(!result);
@@ -80,18 +1014,12 @@ mod test {
 
     #[test]
     fn test_toy_example_with_random_numbers() {
-        let alpha = BigUint::from(4u32);
-        let beta = BigUint::from(9u32);
-        let p = BigUint::from(23u32);
-        let q = BigUint::from(11u32);
-
-        let zkp = ZKP{p, q, alpha, beta};
+        let zkp = ZKP::toy();
 
         let x = BigUint::from(6u32);
+        let c = zkp.generate_challenge();
         let k = ZKP::generate_rand_below(&zkp.q);
 
-        let c = ZKP::generate_rand_below(&zkp.q);
-
         let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
         let y2 = ZKP::exponentiate(&zkp.beta, &x, &zkp.p);
         assert_eq!(y1, BigUint::from(2u32));
@@ -100,7 +1028,7 @@ mod test {
         let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
         let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
 
-        let s = zkp.solve(&k, &c, &x);
+        let s = zkp.solve(&k, &c, &x).unwrap();
 
         let result = zkp.verify(&r1, &r2, &y1, &y2,&c, &s);
         assert!(result);
@@ -108,22 +1036,7 @@ mod test {
 
     #[test]
     fn test_1024_bit_constants() {
-        // https://datatracker.ietf.org/doc/html/rfc5114#section-2.1
-
-        let p_hex = hex::decode("B10B8F96A080E01DDE92DE5EAE5D54EC52C99FBCFB06A3C69A6A9DCA52D23B616073E28675A23D189838EF1E2EE652C013ECB4AEA906112324975C3CD49B83BFACCBDD7D90C4BD7098488E9C219A73724EFFD6FAE5644738FAA31A4FF55BCCC0A151AF5F0DC8B4BD45BF37DF365C1A65E68CFDA76D4DA708DF1FB2BC2E4A4371").expect("coudl not convert to hex");
-        let p = BigUint::from_bytes_be(&p_hex);
-
-        let q_hex = hex::decode("F518AA8781A8DF278ABA4E7D64B7CB9D49462353").expect("could not convert to hex");
-        let q = BigUint::from_bytes_be(&q_hex);
-
-        let alpha_hex = hex::decode("A4D1CBD5C3FD34126765A442EFB99905F8104DD258AC507FD6406CFF14266D31266FEA1E5C41564B777E690F5504F213160217B4B01B886A5E91547F9E2749F4D7FBD7D3B9A92EE1909D0D2263F80A76A6A24C087A091F531DBF0A0169B6A28AD662A4D18E73AFA32D779D5918D08BC8858F4DCEF97C2A24855E6EEB22B3B2E5").expect("could not convert to hex");
-        let alpha = BigUint::from_bytes_be(&alpha_hex);
-
-        // alpha^i is also a generator
-        let beta = alpha.modpow(&ZKP::generate_rand_below(&q), &p);
-       
-        let zkp = ZKP{p, q, alpha, beta};
-        
+        let zkp = ZKP::default_1024();
 
         let x = ZKP::generate_rand_below(&zkp.q);
         let k = ZKP::generate_rand_below(&zkp.q);
@@ -135,10 +1048,778 @@ mod test {
         let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
         let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
 
-        let s = zkp.solve(&k, &c, &x);
+        let s = zkp.solve(&k, &c, &x).unwrap();
 
         let result = zkp.verify(&r1, &r2, &y1, &y2,&c, &s);
         assert!(result);
     }
 
-}
\ No newline at end of file
+    #[test]
+    fn test_2048_bit_constants() {
+        let zkp = ZKP::default_2048();
+        assert_eq!(zkp.p.bits(), 2048);
+        assert_eq!(zkp.q.bits(), 256);
+
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+        let c = ZKP::generate_rand_below(&zkp.q);
+
+        let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
+        let y2 = ZKP::exponentiate(&zkp.beta, &x, &zkp.p);
+
+        let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
+        let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
+
+        let s = zkp.solve(&k, &c, &x).unwrap();
+
+        let result = zkp.verify(&r1, &r2, &y1, &y2,&c, &s);
+        assert!(result);
+
+        let ZKP{p, q, alpha, beta} = zkp;
+        assert!(ZKP::new(p, q, alpha, beta).is_ok());
+    }
+
+    #[test]
+    fn test_new_with_1024_bit_constants() {
+        let ZKP{p, q, alpha, beta} = ZKP::default_1024();
+
+        assert!(ZKP::new(p.clone(), q.clone(), alpha.clone(), beta.clone()).is_ok());
+
+        // broken q: no longer divides p - 1
+        let q_broken = &q + BigUint::from(2u32);
+        let result = ZKP::new(p, q_broken, alpha, beta);
+        assert_eq!(result.err(), Some(ZKPError::InconsistentParameters("q does not divide p - 1".to_string())));
+    }
+
+    #[test]
+    fn test_mixed_group_parameters() {
+        let ZKP{p, alpha, beta, ..} = ZKP::default_1024();
+        let toy_q = ZKP::toy().q;
+
+        let result = ZKP::new(p.clone(), toy_q.clone(), alpha.clone(), beta.clone());
+        assert_eq!(result.err(), Some(ZKPError::InconsistentParameters("q does not divide p - 1".to_string())));
+        let mixed = ZKP{q: toy_q, ..ZKP::default_1024()};
+        assert!(matches!(mixed.validate(), Err(ZKPError::InconsistentParameters(_))));
+
+        // the 2048-bit q with the toy p is above p
+        let mixed = ZKP{q: ZKP::default_2048().q, ..ZKP::toy()};
+        assert_eq!(mixed.validate(), Err(ZKPError::InconsistentParameters("q is not below p".to_string())));
+
+        assert_eq!(ZKP::new(p, BigUint::from(1u32), alpha, beta).err(), Some(ZKPError::InvalidParameter("q must be greater than 1".to_string())));
+    }
+
+    #[test]
+    fn test_new_rejects_bad_toy_parameters() {
+        let ZKP{p, q, alpha, beta} = ZKP::toy();
+        assert!(ZKP::new(p.clone(), q.clone(), alpha.clone(), beta.clone()).is_ok());
+
+        // 21 is not prime
+        assert!(ZKP::new(BigUint::from(21u32), q.clone(), alpha.clone(), beta.clone()).is_err());
+
+        // 5 has order 22 mod 23
+        assert!(ZKP::new(p.clone(), q.clone(), alpha.clone(), BigUint::from(5u32)).is_err());
+
+        // 1 is the identity
+        assert!(ZKP::new(p, q, BigUint::from(1u32), beta).is_err());
+    }
+
+    #[test]
+    fn test_solve_with_unreduced_inputs() {
+        let zkp = ZKP::toy();
+
+        // all three are larger than q = 11
+        let x = BigUint::from(17u32);
+        let k = BigUint::from(29u32);
+        let c = BigUint::from(40u32);
+
+        let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
+        let y2 = ZKP::exponentiate(&zkp.beta, &x, &zkp.p);
+
+        let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
+        let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
+
+        let s = zkp.solve(&k, &c, &x).unwrap();
+        assert!(s < zkp.q);
+        // (29 - 40*17) mod 11 = (7 - 7*6) mod 11 = 9
+        assert_eq!(s, BigUint::from(9u32));
+
+        // verify only accepts a reduced challenge
+        let c = c % &zkp.q;
+        let result = zkp.verify(&r1, &r2, &y1, &y2, &c, &s);
+        assert!(result);
+    }
+
+    #[test]
+    fn test_verify_constant_time_agrees_with_verify() {
+        let zkp = ZKP::toy();
+
+        for _ in 0..500 {
+            let x = ZKP::generate_rand_below(&zkp.q);
+            let k = ZKP::generate_rand_below(&zkp.q);
+            let c = ZKP::generate_rand_below(&zkp.q);
+
+            let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
+            let y2 = ZKP::exponentiate(&zkp.beta, &x, &zkp.p);
+            let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
+            let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
+
+            // random s is sometimes correct, sometimes not
+            let s = ZKP::generate_rand_below(&zkp.q);
+
+            assert_eq!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s), zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c, &s));
+        }
+
+        let zkp = ZKP::default_1024();
+        for _ in 0..20 {
+            let x = ZKP::generate_rand_below(&zkp.q);
+            let k = ZKP::generate_rand_below(&zkp.q);
+            let c = ZKP::generate_rand_below(&zkp.q);
+
+            let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
+            let y2 = ZKP::exponentiate(&zkp.beta, &x, &zkp.p);
+            let r1 = ZKP::exponentiate(&zkp.alpha, &k, &zkp.p);
+            let r2 = ZKP::exponentiate(&zkp.beta, &k, &zkp.p);
+
+            let s = zkp.solve(&k, &c, &x).unwrap();
+            assert!(zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c, &s));
+
+            let s_fake = &s + BigUint::from(1u32);
+            assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s_fake));
+            assert!(!zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c, &s_fake));
+        }
+    }
+
+    #[test]
+    fn test_noninteractive_proof() {
+        let zkp = ZKP::default_1024();
+
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+
+        let commitment = Commitment {
+            y1: ZKP::exponentiate(&zkp.alpha, &x, &zkp.p),
+            y2: ZKP::exponentiate(&zkp.beta, &x, &zkp.p),
+        };
+        let proof = zkp.prove_noninteractive(&x, &k).unwrap();
+        assert!(zkp.verify_noninteractive(&commitment, &proof));
+
+        let one = BigUint::from(1u32);
+
+        let bad_commitment = Commitment { y1: &commitment.y1 + &one, ..commitment.clone() };
+        assert!(!zkp.verify_noninteractive(&bad_commitment, &proof));
+
+        let bad_commitment = Commitment { y2: &commitment.y2 + &one, ..commitment.clone() };
+        assert!(!zkp.verify_noninteractive(&bad_commitment, &proof));
+
+        let bad_proof = Proof { r1: &proof.r1 + &one, ..proof.clone() };
+        assert!(!zkp.verify_noninteractive(&commitment, &bad_proof));
+
+        let bad_proof = Proof { r2: &proof.r2 + &one, ..proof.clone() };
+        assert!(!zkp.verify_noninteractive(&commitment, &bad_proof));
+
+        let bad_proof = Proof { c: &proof.c + &one, ..proof.clone() };
+        assert!(!zkp.verify_noninteractive(&commitment, &bad_proof));
+
+        // same proof against a group with a different beta
+        let other = ZKP{beta: zkp.alpha.modpow(&BigUint::from(3u32), &zkp.p), ..ZKP::default_1024()};
+        assert!(!other.verify_noninteractive(&commitment, &proof));
+    }
+
+    #[test]
+    fn test_noninteractive_proof_with_sha512() {
+        use sha2::Sha512;
+
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+        let (y1, y2) = zkp.register_commitment(&x);
+        let commitment = Commitment{y1, y2};
+
+        let proof_256 = zkp.prove_noninteractive_with::<Sha256>(&x, &k).unwrap();
+        let proof_512 = zkp.prove_noninteractive_with::<Sha512>(&x, &k).unwrap();
+        assert_eq!(proof_256, zkp.prove_noninteractive(&x, &k).unwrap());
+        assert!(&proof_512.c < zkp.q());
+
+        // same r1, r2 for the same k, only c and s depend on the hash
+        assert_eq!((&proof_256.r1, &proof_256.r2), (&proof_512.r1, &proof_512.r2));
+        assert_ne!(proof_256.c, proof_512.c);
+
+        assert!(zkp.verify_noninteractive(&commitment, &proof_256));
+        assert!(zkp.verify_noninteractive_with::<Sha512>(&commitment, &proof_512));
+        assert!(!zkp.verify_noninteractive(&commitment, &proof_512));
+        assert!(!zkp.verify_noninteractive_with::<Sha512>(&commitment, &proof_256));
+    }
+
+    #[test]
+    fn test_generate_group() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let mut rng = ChaCha20Rng::seed_from_u64(7);
+        let zkp = ZKP::generate_group(256, &mut rng).unwrap();
+        assert_eq!(zkp.p.bits(), 256);
+        assert_eq!(zkp.p, &zkp.q * 2u32 + 1u32);
+        assert!(zkp.validate().is_ok());
+        assert_ne!(zkp.alpha, zkp.beta);
+
+        let (x, k, c) = (zkp.generate_challenge_with(&mut rng), zkp.generate_challenge_with(&mut rng), zkp.generate_challenge_with(&mut rng));
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        // small sizes only stop at the lower bound
+        for _ in 0..10 {
+            assert!(ZKP::generate_group(8, &mut rng).unwrap().validate().is_ok());
+        }
+        assert!(matches!(ZKP::generate_group(7, &mut rng), Err(ZKPError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_generate_rand_below_with_seeded_rng() {
+        use rand::SeedableRng;
+        use rand_chacha::ChaCha20Rng;
+
+        let zkp = ZKP::default_1024();
+
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        let a = ZKP::generate_rand_below_with(&mut rng, &zkp.q);
+        let b = ZKP::generate_rand_below_with(&mut rng, &zkp.q);
+        assert!(a < zkp.q);
+        assert_ne!(a, b);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        assert_eq!(ZKP::generate_rand_below_with(&mut rng, &zkp.q), a);
+        assert_eq!(ZKP::generate_rand_below_with(&mut rng, &zkp.q), b);
+
+        let mut rng = ChaCha20Rng::seed_from_u64(42);
+        assert_eq!(ZKP::generate_rand_below_with(&mut rng, &BigUint::from(1000000u32)), BigUint::from(883540u32));
+    }
+
+    #[test]
+    fn test_verify_batch() {
+        let zkp = ZKP::default_1024();
+
+        let mut proofs = Vec::new();
+        for _ in 0..4 {
+            let x = ZKP::generate_rand_below(&zkp.q);
+            let k = ZKP::generate_rand_below(&zkp.q);
+            let c = ZKP::generate_rand_below(&zkp.q);
+
+            let commitment = Commitment {
+                y1: ZKP::exponentiate(&zkp.alpha, &x, &zkp.p),
+                y2: ZKP::exponentiate(&zkp.beta, &x, &zkp.p),
+            };
+            let proof = Proof {
+                r1: ZKP::exponentiate(&zkp.alpha, &k, &zkp.p),
+                r2: ZKP::exponentiate(&zkp.beta, &k, &zkp.p),
+                s: zkp.solve(&k, &c, &x).unwrap(),
+                c,
+            };
+            proofs.push((proof, commitment));
+        }
+
+        // break the second and the last proof
+        proofs[1].0.s += BigUint::from(1u32);
+        proofs[3].1.y2 = BigUint::from(3u32);
+
+        assert_eq!(zkp.verify_batch(&proofs), vec![true, false, true, false]);
+        assert!(zkp.verify_batch(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_prove_bundled() {
+        let zkp = ZKP::default_1024();
+
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+        let c = ZKP::generate_rand_below(&zkp.q);
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        assert_eq!(y1, ZKP::exponentiate(&zkp.alpha, &x, &zkp.p));
+        assert_eq!(y2, ZKP::exponentiate(&zkp.beta, &x, &zkp.p));
+
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn test_verify_rejects_out_of_range_scalars() {
+        let zkp = ZKP::toy();
+
+        let x = BigUint::from(6u32);
+        let k = BigUint::from(7u32);
+        let c = BigUint::from(4u32);
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        // s + q and c + q satisfy the equations since alpha and beta have order q
+        let s_big = &s + &zkp.q;
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s_big));
+        assert!(!zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c, &s_big));
+
+        let c_big = &c + &zkp.q;
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c_big, &s));
+        assert!(!zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c_big, &s));
+    }
+
+    #[test]
+    fn test_from_hex() {
+        let zkp = ZKP::default_1024();
+
+        let p = hex::encode(zkp.p.to_bytes_be());
+        let q = hex::encode(zkp.q.to_bytes_be());
+        let alpha = hex::encode(zkp.alpha.to_bytes_be());
+        let beta = hex::encode(zkp.beta.to_bytes_be());
+
+        let parsed = ZKP::from_hex(&p, &q, &alpha, &beta).unwrap();
+        assert_eq!(parsed.p, zkp.p);
+        assert_eq!(parsed.q, zkp.q);
+        assert_eq!(parsed.alpha, zkp.alpha);
+        assert_eq!(parsed.beta, zkp.beta);
+
+        assert!(matches!(ZKP::from_hex(&p, "not hex", &alpha, &beta), Err(ZKPError::Decode(_))));
+        assert!(matches!(ZKP::from_hex(&p, &q[1..], &alpha, &beta), Err(ZKPError::Decode(_))));
+        assert_eq!(ZKPError::from(hex::decode("zz").unwrap_err()), ZKPError::Decode("Invalid character 'z' at position 0".to_string()));
+        assert!(matches!(ZKPError::from("12x".parse::<BigUint>().unwrap_err()), ZKPError::Decode(_)));
+        assert!(matches!(ZKP::from_hex(&p, "0b", &alpha, &beta), Err(ZKPError::InconsistentParameters(_))));
+    }
+
+    #[test]
+    fn test_fixed_width_encoding() {
+        let zkp = ZKP::default_1024();
+        assert_eq!(zkp.element_width(), 128);
+        assert_eq!(zkp.scalar_width(), 20);
+        assert_eq!(zkp.proof_size(), 296);
+
+        // high-order zero bytes are kept
+        let value = BigUint::from(0x0102u32);
+        let encoded = ZKP::encode_fixed(&value, 4);
+        assert_eq!(encoded, vec![0, 0, 1, 2]);
+        assert_eq!(ZKP::decode_fixed(&encoded, 4).unwrap(), value);
+
+        let zero = BigUint::from(0u32);
+        assert_eq!(ZKP::encode_fixed(&zero, 3), vec![0, 0, 0]);
+        assert_eq!(ZKP::decode_fixed(&[0, 0, 0], 3).unwrap(), zero);
+
+        // one leading zero byte: 127 significant bytes padded to 128
+        let small = &zkp.p >> 8u32;
+        let encoded = ZKP::encode_fixed(&small, zkp.element_width());
+        assert_eq!(encoded.len(), 128);
+        assert_eq!(encoded[0], 0);
+        assert_eq!(ZKP::decode_fixed(&encoded, zkp.element_width()).unwrap(), small);
+
+        assert!(matches!(ZKP::decode_fixed(&[1, 2, 3], 2), Err(ZKPError::Decode(_))));
+        assert_eq!(ZKP::decode_fixed(&[1, 2], 4).unwrap(), value);
+    }
+
+    #[test]
+    fn test_toy_example_single_generator() {
+        let zkp = ZKP::toy();
+
+        let x = BigUint::from(6u32);
+        let k = BigUint::from(7u32);
+
+        let c = BigUint::from(4u32);
+
+        let y1 = ZKP::exponentiate(&zkp.alpha, &x, &zkp.p);
+        assert_eq!(y1, BigUint::from(2u32));
+
+        let (r1, s) = zkp.prove_single(&x, &k, &c).unwrap();
+        assert_eq!(r1, BigUint::from(8u32));
+        assert_eq!(s, BigUint::from(5u32));
+
+        let result = zkp.verify_single(&r1, &y1, &c, &s);
+        assert!(result);
+
+        let x_fake = BigUint::from(7u32);
+        let (_, s_fake) = zkp.prove_single(&x_fake, &k, &c).unwrap();
+
+        let result = zkp.verify_single(&r1, &y1, &c, &s_fake);
+        assert!(!result);
+    }
+
+    #[test]
+    fn test_validate() {
+        assert!(ZKP::default_1024().validate().is_ok());
+        assert!(ZKP::default_2048().validate().is_ok());
+
+        // alpha itself has order q, alpha + 1 almost surely not
+        let zkp = ZKP::default_1024();
+        let wrong_beta = ZKP{beta: &zkp.alpha + BigUint::from(1u32), ..zkp};
+        assert_eq!(wrong_beta.validate(), Err(ZKPError::InvalidParameter("beta does not have order q".to_string())));
+
+        let identity = ZKP{alpha: BigUint::from(1u32), ..ZKP::default_1024()};
+        assert!(identity.validate().is_err());
+
+        // p - 1 has order 2, rejected for prime and for even composite q
+        let zkp = ZKP::default_1024();
+        let order_two = ZKP{alpha: zkp.p() - 1u32, ..zkp.clone()};
+        assert_eq!(order_two.validate(), Err(ZKPError::InvalidParameter("alpha does not have order q".to_string())));
+        assert!(ZKP::new(BigUint::from(23u32), BigUint::from(22u32), BigUint::from(22u32), BigUint::from(5u32)).is_err());
+
+        // q = 22 mod 23: 5 is a primitive root of order 22, 4 only has order 11
+        let composite = ZKP::new(BigUint::from(23u32), BigUint::from(22u32), BigUint::from(5u32), BigUint::from(7u32)).unwrap();
+        assert!(composite.validate().is_ok());
+        let small_order = ZKP{beta: BigUint::from(4u32), ..composite};
+        assert_eq!(small_order.validate(), Err(ZKPError::InvalidParameter("beta has an order below q".to_string())));
+
+        // q = p - 1 with a factor no trial division below 2^16 finds
+        let q = &zkp.p - 1u32;
+        let unfactored = ZKP{q, ..zkp};
+        assert_eq!(unfactored.validate(), Err(ZKPError::InvalidParameter("q is composite and could not be factored".to_string())));
+    }
+
+    #[test]
+    fn test_prime_factors() {
+        assert_eq!(prime_factors(&BigUint::from(22u32)), Some(vec![BigUint::from(2u32), BigUint::from(11u32)]));
+        assert_eq!(prime_factors(&BigUint::from(11u32)), Some(vec![BigUint::from(11u32)]));
+        assert_eq!(prime_factors(&BigUint::from(2u32 * 2 * 3 * 65537)), Some(vec![BigUint::from(2u32), BigUint::from(3u32), BigUint::from(65537u32)]));
+        assert_eq!(prime_factors(&(BigUint::from(65537u32) * 65539u32)), None);
+    }
+
+    #[test]
+    fn test_derive_beta() {
+        let zkp = ZKP::default_1024();
+
+        let beta = ZKP::derive_beta(&zkp.alpha, &zkp.p, &zkp.q, "zkp-chaum-pedersen");
+        assert_eq!(beta, ZKP::derive_beta(&zkp.alpha, &zkp.p, &zkp.q, "zkp-chaum-pedersen"));
+        assert_ne!(beta, ZKP::derive_beta(&zkp.alpha, &zkp.p, &zkp.q, "another domain"));
+
+        let derived = ZKP::new(zkp.p.clone(), zkp.q.clone(), zkp.alpha.clone(), beta).unwrap();
+        assert!(derived.validate().is_ok());
+    }
+
+    #[test]
+    fn test_debug_is_truncated() {
+        let zkp = ZKP::default_1024();
+        let debug = format!("{:?}", zkp);
+
+        assert!(debug.contains("p: b10b8f96..(1024 bits)"));
+        assert!(debug.contains("q: f518aa87..(160 bits)"));
+        assert!(debug.len() < 200);
+        assert_eq!(zkp.to_string(), "1024-bit p, 160-bit q");
+
+        let toy = ZKP::toy();
+        assert_eq!(format!("{:?}", toy), "ZKP { p: 17(5 bits), q: b(4 bits), alpha: 4(3 bits), beta: 9(4 bits), .. }");
+    }
+
+    #[test]
+    fn test_from_named_group() {
+        assert_eq!(ZKP::from_named_group("rfc5114-1024").unwrap().p(), ZKP::default_1024().p());
+        assert_eq!(ZKP::from_named_group("rfc5114-2048").unwrap().p(), ZKP::default_2048().p());
+        for name in ZKP::GROUP_NAMES {
+            assert!(ZKP::from_named_group(name).unwrap().validate().is_ok());
+        }
+
+        assert!(matches!(ZKP::from_named_group("rfc5114-512"), Err(ZKPError::InvalidParameter(_))));
+    }
+
+    #[cfg(feature = "arbitrary")]
+    #[test]
+    fn test_arbitrary_values_are_in_range() {
+        use arbitrary::{Arbitrary, Unstructured};
+
+        let data: Vec<u8> = (0..8192u32).map(|i| (i * 7919 % 251) as u8).collect();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..20 {
+            let zkp = ZKP::arbitrary(&mut u).unwrap();
+            let scalar = zkp.arbitrary_scalar(&mut u).unwrap();
+            assert!(scalar > BigUint::from(0u32) && &scalar < zkp.q());
+
+            // no bounds without a group, the fuzz input is the only limit
+            let proof = Proof::arbitrary(&mut u).unwrap();
+            let commitment = Commitment::arbitrary(&mut u).unwrap();
+            assert!(proof.s.bits() <= 8 * data.len() as u64 && commitment.y1.bits() <= 8 * data.len() as u64);
+        }
+
+        // exhausted input still gives a scalar in range
+        let mut empty = Unstructured::new(&[]);
+        assert_eq!(ZKP::toy().arbitrary_scalar(&mut empty).unwrap(), BigUint::from(1u32));
+        assert!(ZKP::arbitrary(&mut empty).is_ok());
+    }
+
+    #[test]
+    fn test_secret_above_q_is_reduced() {
+        let zkp = ZKP::default_1024();
+        let x = zkp.q() + 3u32;
+        let reduced = BigUint::from(3u32);
+        let (k, c) = (zkp.generate_challenge(), zkp.generate_challenge());
+
+        assert_eq!(zkp.register_commitment(&x), zkp.register_commitment(&reduced));
+        assert_eq!(zkp.solve(&k, &c, &x).unwrap(), zkp.solve(&k, &c, &reduced).unwrap());
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert!(s < *zkp.q());
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn test_recover_x_from_leaked_nonce() {
+        let zkp = ZKP::default_1024();
+        let x = BigUint::from(0xdeadbeefu32);
+        let (k, c) = (zkp.generate_challenge(), zkp.generate_challenge());
+
+        let s = zkp.solve(&k, &c, &x).unwrap();
+        assert_eq!(zkp.expected_s(&k, &c, &x).unwrap(), s);
+        assert_eq!(zkp.recover_x(&k, &c, &s).unwrap(), x);
+
+        // toy group: s = (29 - 40*17) mod 11 = 9, and back
+        let toy = ZKP::toy();
+        let s = toy.solve(&BigUint::from(29u32), &BigUint::from(40u32), &BigUint::from(17u32)).unwrap();
+        assert_eq!(toy.recover_x(&BigUint::from(29u32), &BigUint::from(40u32), &s).unwrap(), BigUint::from(17u32 % 11));
+
+        assert!(matches!(toy.recover_x(&BigUint::from(29u32), &BigUint::from(22u32), &s), Err(ZKPError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_keyed_challenge() {
+        let zkp = ZKP::default_2048();
+        let c = zkp.keyed_challenge(b"server key", "auth id", b"salt", 1_700_000_000_000);
+        assert!(c > BigUint::from(0u32) && &c < zkp.q());
+
+        // recomputing from the same inputs gives the same c, every input matters
+        assert_eq!(zkp.keyed_challenge(b"server key", "auth id", b"salt", 1_700_000_000_000), c);
+        assert_ne!(zkp.keyed_challenge(b"other key", "auth id", b"salt", 1_700_000_000_000), c);
+        assert_ne!(zkp.keyed_challenge(b"server key", "auth id 2", b"salt", 1_700_000_000_000), c);
+        assert_ne!(zkp.keyed_challenge(b"server key", "auth id", b"pepper", 1_700_000_000_000), c);
+        assert_ne!(zkp.keyed_challenge(b"server key", "auth id", b"salt", 1_700_000_000_001), c);
+        // the auth id cannot absorb the start of the salt
+        assert_ne!(zkp.keyed_challenge(b"server key", "auth ids", b"alt", 1_700_000_000_000), c);
+
+        let toy = ZKP::toy();
+        for timestamp in 0..50 {
+            let c = toy.keyed_challenge(b"server key", "auth id", b"salt", timestamp);
+            assert!(c > BigUint::from(0u32) && &c < toy.q());
+        }
+    }
+
+    #[test]
+    fn test_group_fingerprint() {
+        let zkp = ZKP::default_1024();
+        let same = ZKP::new(zkp.p.clone(), zkp.q.clone(), zkp.alpha.clone(), zkp.beta.clone()).unwrap();
+        assert!(zkp.same_group(&same));
+        assert_eq!(zkp.group_fingerprint(), same.group_fingerprint());
+
+        let beta = ZKP::derive_beta(&zkp.alpha, &zkp.p, &zkp.q, "zkp-chaum-pedersen");
+        let other_beta = ZKP::new(zkp.p.clone(), zkp.q.clone(), zkp.alpha.clone(), beta).unwrap();
+        assert!(!zkp.same_group(&other_beta));
+        assert_ne!(zkp.group_fingerprint(), other_beta.group_fingerprint());
+
+        assert_ne!(zkp.group_fingerprint(), ZKP::default_2048().group_fingerprint());
+        assert_ne!(ZKP::toy().group_fingerprint(), ZKP::default_1024().group_fingerprint());
+    }
+
+    #[test]
+    fn test_is_subgroup_element() {
+        let zkp = ZKP::toy();
+        // the subgroup of order 11 mod 23 is the quadratic residues
+        for y in [1u32, 2, 3, 4, 6, 8, 9, 12, 13, 16, 18] {
+            assert!(zkp.is_subgroup_element(&BigUint::from(y)));
+        }
+        for y in [0u32, 5, 7, 10, 11, 14, 15, 17, 19, 20, 21, 22] {
+            assert!(!zkp.is_subgroup_element(&BigUint::from(y)));
+        }
+
+        let zkp = ZKP::default_1024();
+        let (y1, _) = zkp.register_commitment(&zkp.generate_challenge());
+        assert!(zkp.is_subgroup_element(&y1));
+        // p - 1 has order 2
+        assert!(!zkp.is_subgroup_element(&(zkp.p() - 1u32)));
+    }
+
+    #[test]
+    fn test_authenticate_local() {
+        let toy = ZKP::toy();
+        for x in 0..30u32 {
+            assert!(authenticate_local(&toy, &BigUint::from(x)));
+        }
+
+        let zkp = ZKP::default_1024();
+        assert!(authenticate_local(&zkp, &zkp.generate_challenge()));
+        assert!(authenticate_local(&zkp, &(zkp.q() + 3u32)));
+    }
+
+    #[test]
+    fn test_default_1024_is_cached() {
+        let fresh = ZKP::rfc5114_1024().unwrap();
+        assert_eq!(ZKP::default_1024(), fresh);
+        // the second call is served from the same cache
+        assert_eq!(ZKP::default_1024(), *RFC5114_1024);
+        assert!(RFC5114_1024.validate().is_ok());
+    }
+
+    #[test]
+    fn test_zero_challenge_is_rejected() {
+        let zkp = ZKP::toy();
+        let zero = BigUint::from(0u32);
+
+        // with c = 0, r1 = alpha^s and r2 = beta^s pass for any y1, y2
+        let s = BigUint::from(3u32);
+        let (r1, r2) = zkp.register_commitment(&s);
+        let (y1, y2) = zkp.register_commitment(&BigUint::from(6u32));
+        assert!(!zkp.verify(&r1, &r2, &y1, &y2, &zero, &s));
+        assert!(!zkp.verify_constant_time(&r1, &r2, &y1, &y2, &zero, &s));
+        assert!(!zkp.verify_single(&r1, &y1, &zero, &s));
+
+        // s = 0 is an honest answer for k = c*x mod q: 2*6 mod 11 = 1
+        let (k, c) = (BigUint::from(1u32), BigUint::from(2u32));
+        let (r1, r2, s) = zkp.prove(&BigUint::from(6u32), &k, &c).unwrap();
+        assert_eq!(s, zero);
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+        assert!(zkp.verify_constant_time(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn test_generate_challenge_is_never_zero() {
+        use rand::SeedableRng;
+
+        let zkp = ZKP::toy();
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(7);
+
+        for _ in 0..10_000 {
+            let c = zkp.generate_challenge_with(&mut rng);
+            assert!(c > BigUint::from(0u32) && &c < zkp.q());
+        }
+    }
+
+    #[test]
+    fn test_clone_verifies_the_same_proof() {
+        let zkp = ZKP::default_1024();
+        let cloned = zkp.clone();
+        assert_eq!(zkp, cloned);
+        assert_ne!(zkp, ZKP::default_2048());
+
+        let x = ZKP::generate_rand_below(zkp.q());
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+        let proof = zkp.prove_noninteractive(&x, &k).unwrap();
+
+        assert!(cloned.verify_noninteractive(&Commitment{y1, y2}, &proof));
+    }
+
+    #[test]
+    fn test_random_element_is_in_subgroup() {
+        use rand::SeedableRng;
+
+        let one = BigUint::from(1u32);
+        let mut rng = rand_chacha::ChaCha20Rng::seed_from_u64(11);
+
+        for zkp in [ZKP::toy(), ZKP::default_1024()] {
+            for _ in 0..20 {
+                let element = zkp.random_element(&mut rng);
+                assert!(element != one && &element < zkp.p());
+                assert_eq!(element.modpow(zkp.q(), zkp.p()), one);
+            }
+        }
+    }
+
+    #[test]
+    fn test_challenge_bound() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2) = zkp.register_commitment(&k);
+
+        let c1 = zkp.challenge_bound_with_salt(b"salt 1", &r1, &r2, &y1, &y2);
+        let c2 = zkp.challenge_bound_with_salt(b"salt 2", &r1, &r2, &y1, &y2);
+        assert_ne!(c1, c2);
+        assert_eq!(c1, zkp.challenge_bound_with_salt(b"salt 1", &r1, &r2, &y1, &y2));
+        assert_ne!(c1, zkp.challenge_bound_with_salt(b"salt 1", &r2, &r1, &y1, &y2));
+
+        let c = zkp.challenge_bound(&r1, &r2, &y1, &y2);
+        assert!(c > BigUint::from(0u32) && &c < zkp.q());
+        let s = zkp.solve(&k, &c, &x).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn test_new_secure() {
+        let ZKP{p, q, alpha, beta} = ZKP::toy();
+        assert!(matches!(ZKP::new_secure(p, q, alpha, beta), Err(ZKPError::WeakParameters(_))));
+
+        let ZKP{p, q, alpha, beta} = ZKP::default_1024();
+        assert!(matches!(ZKP::new_secure(p, q, alpha, beta), Err(ZKPError::WeakParameters(_))));
+
+        let ZKP{p, q, alpha, beta} = ZKP::default_2048();
+        assert!(ZKP::new_secure(p, q, alpha, beta).is_ok());
+    }
+
+    #[test]
+    fn test_element_from_bytes() {
+        let zkp = ZKP::default_1024();
+
+        let p_minus_one = zkp.p() - BigUint::from(1u32);
+        assert_eq!(zkp.element_from_bytes(&p_minus_one.to_bytes_be()), Ok(p_minus_one));
+        assert_eq!(zkp.element_from_bytes(&[2]), Ok(BigUint::from(2u32)));
+
+        assert!(matches!(zkp.element_from_bytes(&zkp.p().to_bytes_be()), Err(ZKPError::InvalidParameter(_))));
+        assert!(matches!(zkp.element_from_bytes(&[0xff; 200]), Err(ZKPError::InvalidParameter(_))));
+        assert!(matches!(zkp.element_from_bytes(&[0; 200]), Err(ZKPError::InvalidParameter(_))));
+
+        assert_eq!(zkp.scalar_from_bytes(&zkp.q().to_bytes_be()), Ok(zkp.q().clone()));
+        assert!(matches!(zkp.scalar_from_bytes(&[0; 21]), Err(ZKPError::InvalidParameter(_))));
+    }
+
+    #[test]
+    fn test_deterministic_proof() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let k = zkp.deterministic_nonce(&x, b"alice");
+        assert_eq!(k, zkp.deterministic_nonce(&x, b"alice"));
+        assert_eq!(k, zkp.deterministic_nonce(&(&x + zkp.q()), b"alice"));
+        assert_ne!(k, zkp.deterministic_nonce(&x, b"bob"));
+        assert!(&k < zkp.q());
+
+        let proof = zkp.prove_noninteractive_deterministic(&x, b"alice").unwrap();
+        assert_eq!(proof, zkp.prove_noninteractive_deterministic(&x, b"alice").unwrap());
+        assert!(zkp.verify_noninteractive(&Commitment{y1, y2}, &proof));
+    }
+
+    #[test]
+    fn test_verify_detailed() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+        let c = zkp.generate_challenge();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert_eq!(zkp.verify_detailed(&r1, &r2, &y1, &y2, &c, &s), VerifyOutcome::Ok);
+
+        let corrupted = (&y2 * &zkp.beta) % &zkp.p;
+        assert_eq!(zkp.verify_detailed(&r1, &r2, &y1, &corrupted, &c, &s), VerifyOutcome::Cond2Failed);
+        assert!(!zkp.verify(&r1, &r2, &y1, &corrupted, &c, &s));
+
+        let corrupted = (&y1 * &zkp.alpha) % &zkp.p;
+        assert_eq!(zkp.verify_detailed(&r1, &r2, &corrupted, &y2, &c, &s), VerifyOutcome::Cond1Failed);
+
+        let s_fake = zkp.solve(&k, &c, &(&x + BigUint::from(1u32))).unwrap();
+        assert_eq!(zkp.verify_detailed(&r1, &r2, &y1, &y2, &c, &s_fake), VerifyOutcome::BothFailed);
+    }
+
+    #[test]
+    fn test_transcript_replay() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(&zkp.q);
+        let k = ZKP::generate_rand_below(&zkp.q);
+        let c = zkp.generate_challenge();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        let transcript = Transcript::new(Commitment{y1, y2}, Proof{r1, r2, c, s});
+
+        let json = serde_json::to_string(&transcript).unwrap();
+        let replayed: Transcript = serde_json::from_str(&json).unwrap();
+        assert_eq!(replayed, transcript);
+        assert_eq!(zkp.verify_transcript(&replayed), VerifyOutcome::Ok);
+
+        let tampered = Transcript{s: (&replayed.s + 1u32) % &zkp.q, ..replayed};
+        assert_eq!(zkp.verify_transcript(&tampered), VerifyOutcome::BothFailed);
+    }
+}
@@ -0,0 +1,97 @@
+use std::fmt;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Counters kept by `AuthImpl`, read them with `AuthImpl::metrics`
+///
+/// Every counter only goes up, so they map directly to Prometheus counters
+#[derive(Debug, Default)]
+pub struct Metrics {
+    registrations: AtomicU64,
+    challenges: AtomicU64,
+    verifications_succeeded: AtomicU64,
+    verifications_failed: AtomicU64,
+    sessions_issued: AtomicU64,
+}
+
+/// Values of `Metrics` at one point in time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct MetricsSnapshot {
+    pub registrations: u64,
+    pub challenges: u64,
+    pub verifications_succeeded: u64,
+    pub verifications_failed: u64,
+    pub sessions_issued: u64,
+}
+
+impl Metrics {
+    pub(crate) fn registered(&self) {
+        self.registrations.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn challenge_issued(&self) {
+        self.challenges.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Rejected proofs only, unknown or expired challenges are not counted
+    pub(crate) fn verified(&self, succeeded: bool) {
+        let counter = if succeeded { &self.verifications_succeeded } else { &self.verifications_failed };
+        counter.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub(crate) fn session_issued(&self) {
+        self.sessions_issued.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Counters are read one by one, a request finishing in between can show up
+    /// in some of them only
+    pub fn snapshot(&self) -> MetricsSnapshot {
+        MetricsSnapshot {
+            registrations: self.registrations.load(Ordering::Relaxed),
+            challenges: self.challenges.load(Ordering::Relaxed),
+            verifications_succeeded: self.verifications_succeeded.load(Ordering::Relaxed),
+            verifications_failed: self.verifications_failed.load(Ordering::Relaxed),
+            sessions_issued: self.sessions_issued.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Prometheus text exposition format, e.g. for a `/metrics` endpoint
+impl fmt::Display for MetricsSnapshot {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let counters = [
+            ("zkp_registrations_total", "Users registered", self.registrations),
+            ("zkp_challenges_total", "Authentication challenges issued", self.challenges),
+            ("zkp_verifications_succeeded_total", "Proofs accepted", self.verifications_succeeded),
+            ("zkp_verifications_failed_total", "Proofs rejected", self.verifications_failed),
+            ("zkp_sessions_issued_total", "Session tokens issued", self.sessions_issued),
+        ];
+
+        for (name, help, value) in counters {
+            writeln!(f, "# HELP {} {}", name, help)?;
+            writeln!(f, "# TYPE {} counter", name)?;
+            writeln!(f, "{} {}", name, value)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_snapshot_and_text_format() {
+        let metrics = Metrics::default();
+        metrics.registered();
+        metrics.verified(false);
+        metrics.verified(false);
+
+        let snapshot = metrics.snapshot();
+        assert_eq!(snapshot, MetricsSnapshot{registrations: 1, verifications_failed: 2, ..Default::default()});
+
+        let text = snapshot.to_string();
+        assert!(text.contains("# TYPE zkp_registrations_total counter\nzkp_registrations_total 1\n"));
+        assert!(text.contains("zkp_verifications_failed_total 2\n"));
+        assert!(text.contains("zkp_sessions_issued_total 0\n"));
+    }
+}
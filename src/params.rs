@@ -0,0 +1,58 @@
+use num_bigint::BigUint;
+
+use crate::ZKP;
+
+/// Public values a prover needs to compute y1, y2, r1 and r2, without q or
+/// anything verifier specific
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Params {
+    pub p: BigUint,
+    pub alpha: BigUint,
+    pub beta: BigUint,
+}
+
+impl Params {
+    pub fn new(p: BigUint, alpha: BigUint, beta: BigUint) -> Self {
+        Params{p, alpha, beta}
+    }
+
+    /// output = (y1, y2) = (alpha^x mod p, beta^x mod p)
+    pub fn commitments(&self, x: &BigUint) -> (BigUint, BigUint) {
+        (ZKP::exponentiate(&self.alpha, x, &self.p), ZKP::exponentiate(&self.beta, x, &self.p))
+    }
+
+    /// output = (r1, r2) = (alpha^k mod p, beta^k mod p)
+    pub fn responses(&self, k: &BigUint) -> (BigUint, BigUint) {
+        (ZKP::exponentiate(&self.alpha, k, &self.p), ZKP::exponentiate(&self.beta, k, &self.p))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_params_match_zkp() {
+        let zkp = ZKP::default_1024();
+        let params = Params::new(zkp.p().clone(), zkp.alpha().clone(), zkp.beta().clone());
+        assert_eq!(params, zkp.params());
+
+        let x = ZKP::generate_rand_below(zkp.q());
+        let k = ZKP::generate_rand_below(zkp.q());
+        let c = ZKP::generate_rand_below(zkp.q());
+
+        assert_eq!(params.commitments(&x), zkp.register_commitment(&x));
+
+        let (r1, r2) = params.responses(&k);
+        let (r1_zkp, r2_zkp, _) = zkp.prove(&x, &k, &c).unwrap();
+        assert_eq!((r1, r2), (r1_zkp, r2_zkp));
+    }
+
+    #[test]
+    fn test_toy_params() {
+        let params = Params::new(BigUint::from(23u32), BigUint::from(4u32), BigUint::from(9u32));
+
+        assert_eq!(params.commitments(&BigUint::from(6u32)), (BigUint::from(2u32), BigUint::from(3u32)));
+        assert_eq!(params.responses(&BigUint::from(7u32)), (BigUint::from(8u32), BigUint::from(4u32)));
+    }
+}
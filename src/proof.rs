@@ -0,0 +1,226 @@
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::{ZKPError, ZKP};
+
+/// Public commitments registered by the prover
+/// y1 = alpha^x mod p
+/// y2 = beta^x mod p
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Commitment {
+    #[serde(with = "biguint_hex")]
+    pub y1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub y2: BigUint,
+}
+
+/// One run of the protocol
+/// r1 = alpha^k mod p
+/// r2 = beta^k mod p
+/// s = k - c*x mod q
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Proof {
+    #[serde(with = "biguint_hex")]
+    pub r1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r2: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub c: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub s: BigUint,
+}
+
+/// Every value of one run, e.g. recorded for an audit log and replayed with
+/// `ZKP::verify_transcript`
+/// y1 = alpha^x mod p, y2 = beta^x mod p
+/// r1 = alpha^k mod p, r2 = beta^k mod p
+/// s = k - c*x mod q
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "arbitrary", derive(arbitrary::Arbitrary))]
+pub struct Transcript {
+    #[serde(with = "biguint_hex")]
+    pub y1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub y2: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r2: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub c: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub s: BigUint,
+}
+
+impl Transcript {
+    pub fn new(commitment: Commitment, proof: Proof) -> Self {
+        Transcript {
+            y1: commitment.y1,
+            y2: commitment.y2,
+            r1: proof.r1,
+            r2: proof.r2,
+            c: proof.c,
+            s: proof.s,
+        }
+    }
+}
+
+/// Binary wire format in the 1024-bit group the service uses by default,
+/// see `ZKP::decode_proof` for other groups
+impl TryFrom<&[u8]> for Proof {
+    type Error = ZKPError;
+
+    fn try_from(bytes: &[u8]) -> Result<Self, Self::Error> {
+        ZKP::default_1024().decode_proof(bytes)
+    }
+}
+
+/// `ZKP::encode_proof` in the 1024-bit group
+impl From<&Proof> for Vec<u8> {
+    fn from(proof: &Proof) -> Self {
+        ZKP::default_1024().encode_proof(proof)
+    }
+}
+
+impl From<Proof> for Vec<u8> {
+    fn from(proof: Proof) -> Self {
+        Vec::from(&proof)
+    }
+}
+
+/// Encodes a `BigUint` as a big-endian hex string
+pub(crate) mod biguint_hex {
+    use num_bigint::BigUint;
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &BigUint, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(value.to_bytes_be()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<BigUint, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        let bytes = hex::decode(&encoded).map_err(D::Error::custom)?;
+        Ok(BigUint::from_bytes_be(&bytes))
+    }
+}
+
+/// `biguint_hex` for an optional value, use with `#[serde(default)]` so a
+/// missing field reads as `None`
+pub(crate) mod biguint_hex_option {
+    use num_bigint::BigUint;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &Option<BigUint>, serializer: S) -> Result<S::Ok, S::Error> {
+        match value {
+            Some(value) => super::biguint_hex::serialize(value, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<BigUint>, D::Error> {
+        let encoded = Option::<String>::deserialize(deserializer)?;
+        encoded.map(|encoded| super::biguint_hex::deserialize(serde::de::value::StringDeserializer::new(encoded))).transpose()
+    }
+}
+
+/// Encodes bytes as a hex string
+pub(crate) mod bytes_hex {
+    use serde::{de::Error, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(value: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&hex::encode(value))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        hex::decode(&encoded).map_err(D::Error::custom)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_commitment_json_round_trip() {
+        let commitment = Commitment {
+            y1: BigUint::from(2u32),
+            y2: BigUint::from(3u32),
+        };
+
+        let json = serde_json::to_string(&commitment).unwrap();
+        assert_eq!(json, r#"{"y1":"02","y2":"03"}"#);
+        assert_eq!(serde_json::from_str::<Commitment>(&json).unwrap(), commitment);
+    }
+
+    #[test]
+    fn test_proof_json_round_trip() {
+        let zkp = ZKP::default_1024();
+
+        let x = ZKP::generate_rand_below(zkp.q());
+        let k = ZKP::generate_rand_below(zkp.q());
+        let c = ZKP::generate_rand_below(zkp.q());
+
+        let proof = Proof {
+            r1: ZKP::exponentiate(zkp.alpha(), &k, zkp.p()),
+            r2: ZKP::exponentiate(zkp.beta(), &k, zkp.p()),
+            s: zkp.solve(&k, &c, &x).unwrap(),
+            c,
+        };
+
+        let json = serde_json::to_string(&proof).unwrap();
+        assert_eq!(serde_json::from_str::<Proof>(&json).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_deserialize_rejects_bad_hex() {
+        assert!(serde_json::from_str::<Commitment>(r#"{"y1":"zz","y2":"03"}"#).is_err());
+    }
+
+    #[test]
+    fn test_proof_bytes_round_trip() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let proof = zkp.prove_noninteractive(&x, &ZKP::generate_rand_below(zkp.q())).unwrap();
+
+        let bytes: Vec<u8> = (&proof).into();
+        assert_eq!(bytes.len(), zkp.proof_size());
+        assert_eq!(Proof::try_from(bytes.as_slice()).unwrap(), proof);
+
+        // small values keep their offsets
+        let small = Proof {
+            r1: BigUint::from(1u32),
+            r2: BigUint::from(2u32),
+            c: BigUint::from(3u32),
+            s: BigUint::from(0u32),
+        };
+        let bytes: Vec<u8> = small.clone().into();
+        assert_eq!((bytes[127], bytes[255], bytes[275], bytes[295]), (1, 2, 3, 0));
+        assert_eq!(Proof::try_from(bytes.as_slice()).unwrap(), small);
+
+        let zkp = ZKP::default_2048();
+        let bytes = zkp.encode_proof(&proof);
+        assert_eq!(bytes.len(), zkp.proof_size());
+        assert_eq!(zkp.decode_proof(&bytes).unwrap(), proof);
+    }
+
+    #[test]
+    fn test_truncated_proof_bytes_are_rejected() {
+        let bytes: Vec<u8> = Proof {
+            r1: BigUint::from(1u32),
+            r2: BigUint::from(2u32),
+            c: BigUint::from(3u32),
+            s: BigUint::from(4u32),
+        }
+        .into();
+
+        assert!(matches!(Proof::try_from(&bytes[..bytes.len() - 1]), Err(ZKPError::Decode(_))));
+        assert!(matches!(Proof::try_from(&[][..]), Err(ZKPError::Decode(_))));
+
+        let mut longer = bytes.clone();
+        longer.push(0);
+        assert!(matches!(Proof::try_from(longer.as_slice()), Err(ZKPError::Decode(_))));
+    }
+}
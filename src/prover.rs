@@ -0,0 +1,134 @@
+use std::collections::HashSet;
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha256};
+
+use crate::{Secret, ZKPError, ZKP};
+
+/// Prover side of the protocol that refuses to answer two challenges with the
+/// same nonce k, which would leak x
+///
+/// Only SHA-256 digests of the used nonces are kept, never the nonces.
+pub struct Prover {
+    zkp: ZKP,
+    used_nonces: HashSet<[u8; 32]>,
+}
+
+impl Prover {
+    pub fn new(zkp: ZKP) -> Self {
+        Prover {
+            zkp,
+            used_nonces: HashSet::new(),
+        }
+    }
+
+    pub fn zkp(&self) -> &ZKP {
+        &self.zkp
+    }
+
+    /// output = s = k - c*x mod q, or `NonceReused` if k was seen before
+    pub fn solve(&mut self, k: &BigUint, c: &BigUint, x: &BigUint) -> Result<BigUint, ZKPError> {
+        self.use_nonce(k)?;
+        self.zkp.solve(k, c, x)
+    }
+
+    /// Same as `ZKP::prove`, or `NonceReused` if k was seen before
+    pub fn prove(&mut self, x: &BigUint, k: &BigUint, c: &BigUint) -> Result<(BigUint, BigUint, BigUint), ZKPError> {
+        self.use_nonce(k)?;
+        self.zkp.prove(x, k, c)
+    }
+
+    /// k and k + q are the same nonce, so the digest is taken over k mod q
+    fn use_nonce(&mut self, k: &BigUint) -> Result<(), ZKPError> {
+        let k = k % self.zkp.q();
+        let digest: [u8; 32] = Sha256::digest(ZKP::encode_fixed(&k, self.zkp.scalar_width())).into();
+
+        if !self.used_nonces.insert(digest) {
+            return Err(ZKPError::NonceReused);
+        }
+        Ok(())
+    }
+}
+
+/// One run of the protocol with a nonce k drawn internally, see
+/// `ZKP::prove_with_auto_nonce`
+///
+/// k never leaves the session: `respond` consumes it so every k answers exactly
+/// one challenge, and k is zeroized when the session is dropped
+pub struct ProofSession<'a> {
+    zkp: &'a ZKP,
+    x: &'a BigUint,
+    k: Secret,
+    r1: BigUint,
+    r2: BigUint,
+}
+
+impl<'a> ProofSession<'a> {
+    pub(crate) fn new(zkp: &'a ZKP, x: &'a BigUint) -> Self {
+        let k = Secret::new(ZKP::generate_rand_below(zkp.q()));
+        let r1 = ZKP::exponentiate(zkp.alpha(), &k, zkp.p());
+        let r2 = ZKP::exponentiate(zkp.beta(), &k, zkp.p());
+
+        ProofSession{zkp, x, k, r1, r2}
+    }
+
+    /// r1 = alpha^k mod p, sent to the verifier before the challenge
+    pub fn r1(&self) -> &BigUint {
+        &self.r1
+    }
+
+    /// r2 = beta^k mod p, sent to the verifier before the challenge
+    pub fn r2(&self) -> &BigUint {
+        &self.r2
+    }
+
+    /// output = s = k - c*x mod q
+    pub fn respond(self, c: &BigUint) -> Result<BigUint, ZKPError> {
+        self.zkp.solve(&self.k, c, self.x)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_nonce_reuse_is_rejected() {
+        let mut prover = Prover::new(ZKP::default_1024());
+        let q = prover.zkp().q().clone();
+
+        let x = ZKP::generate_rand_below(&q);
+        let k = ZKP::generate_rand_below(&q);
+        let c1 = ZKP::generate_rand_below(&q);
+        let c2 = ZKP::generate_rand_below(&q);
+
+        assert!(prover.solve(&k, &c1, &x).is_ok());
+        assert_eq!(prover.solve(&k, &c2, &x), Err(ZKPError::NonceReused));
+
+        // same nonce in disguise
+        assert_eq!(prover.prove(&x, &(&k + &q), &c2), Err(ZKPError::NonceReused));
+
+        let k2 = ZKP::generate_rand_below(&q);
+        assert!(prover.prove(&x, &k2, &c2).is_ok());
+    }
+
+    #[test]
+    fn test_proof_session() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        // commit
+        let session = zkp.prove_with_auto_nonce(&x);
+        let (r1, r2) = (session.r1().clone(), session.r2().clone());
+
+        // challenge, then respond
+        let c = zkp.generate_challenge();
+        let s = session.respond(&c).unwrap();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+
+        // every session draws its own k
+        let other = zkp.prove_with_auto_nonce(&x);
+        assert_ne!(other.r1(), &r1);
+    }
+}
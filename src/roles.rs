@@ -0,0 +1,174 @@
+//! The two roles of the protocol as traits, so a transport can drive any prover
+//! and verifier the same way: the prover sends y1, y2 and r1, r2, the verifier
+//! answers with c, the prover with s, and the verifier checks the result
+
+use num_bigint::BigUint;
+
+use crate::{Commitment, Proof, Secret, ZKPError, ZKP};
+
+/// Prover side of any number of runs for one secret x
+pub trait ProverRole {
+    /// y1, y2 the verifier checks proofs against
+    fn commitment(&self) -> Commitment;
+
+    /// Starts a run with a fresh nonce k
+    /// output = (r1, r2) = (alpha^k mod p, beta^k mod p)
+    fn commit(&mut self) -> (BigUint, BigUint);
+
+    /// output = s = k - c*x mod q for the k of the last `commit`, which is used
+    /// up. Errors if no run is open
+    fn respond(&mut self, c: &BigUint) -> Result<BigUint, ZKPError>;
+}
+
+/// Verifier side of any number of runs, `Verifier` is the implementation with
+/// an injectable challenge source
+pub trait VerifierRole {
+    fn zkp(&self) -> &ZKP;
+
+    /// Challenge c in [1, q) for the prover registered with `commitment`
+    fn challenge(&mut self, commitment: &Commitment) -> BigUint;
+
+    /// Same as `ZKP::verify`, `proof.c` must be the challenge this verifier issued
+    fn verify(&self, commitment: &Commitment, proof: &Proof) -> bool {
+        self.zkp().verify(&proof.r1, &proof.r2, &commitment.y1, &commitment.y2, &proof.c, &proof.s)
+    }
+}
+
+/// `ProverRole` for x in the group of a `ZKP`, drawing k like `ZKP::prove_with_auto_nonce`
+pub struct ZKPProver {
+    zkp: ZKP,
+    x: Secret,
+    /// nonce of the open run
+    k: Option<Secret>,
+}
+
+impl ZKPProver {
+    pub fn new(zkp: ZKP, x: Secret) -> Self {
+        ZKPProver{zkp, x, k: None}
+    }
+}
+
+impl ProverRole for ZKPProver {
+    fn commitment(&self) -> Commitment {
+        let (y1, y2) = self.zkp.register_commitment(&self.x);
+        Commitment{y1, y2}
+    }
+
+    fn commit(&mut self) -> (BigUint, BigUint) {
+        let k = Secret::new(ZKP::generate_rand_below(self.zkp.q()));
+        let r = self.zkp.register_commitment(&k);
+        self.k = Some(k);
+        r
+    }
+
+    fn respond(&mut self, c: &BigUint) -> Result<BigUint, ZKPError> {
+        let k = self.k.take().ok_or_else(|| ZKPError::InvalidParameter("respond without an open commit".to_string()))?;
+        self.zkp.solve(&k, c, &self.x)
+    }
+}
+
+/// Draws every challenge uniformly from [1, q), like the gRPC service
+impl VerifierRole for ZKP {
+    fn zkp(&self) -> &ZKP {
+        self
+    }
+
+    fn challenge(&mut self, _commitment: &Commitment) -> BigUint {
+        self.generate_challenge()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Verifier;
+    use std::collections::VecDeque;
+
+    /// In-memory transport: every message goes through a queue as bytes, the
+    /// way it would cross a socket
+    #[derive(Default)]
+    struct MockTransport {
+        queue: VecDeque<Vec<u8>>,
+    }
+
+    impl MockTransport {
+        fn send(&mut self, value: &BigUint) {
+            self.queue.push_back(value.to_bytes_be());
+        }
+
+        fn recv(&mut self) -> BigUint {
+            BigUint::from_bytes_be(&self.queue.pop_front().expect("message sent"))
+        }
+    }
+
+    /// One run between any prover and verifier over the transport
+    fn run(prover: &mut impl ProverRole, verifier: &mut impl VerifierRole) -> bool {
+        let mut transport = MockTransport::default();
+
+        let commitment = prover.commitment();
+        let (r1, r2) = prover.commit();
+        for value in [&commitment.y1, &commitment.y2, &r1, &r2] {
+            transport.send(value);
+        }
+
+        let commitment = Commitment{y1: transport.recv(), y2: transport.recv()};
+        let (r1, r2) = (transport.recv(), transport.recv());
+        let c = verifier.challenge(&commitment);
+        transport.send(&c);
+
+        let s = prover.respond(&transport.recv()).unwrap();
+        transport.send(&s);
+
+        verifier.verify(&commitment, &Proof{r1, r2, c, s: transport.recv()})
+    }
+
+    /// Claims the commitment of x but answers with another secret
+    struct CheatingProver {
+        honest: ZKPProver,
+        wrong: ZKPProver,
+    }
+
+    impl ProverRole for CheatingProver {
+        fn commitment(&self) -> Commitment {
+            self.honest.commitment()
+        }
+
+        fn commit(&mut self) -> (BigUint, BigUint) {
+            self.wrong.commit()
+        }
+
+        fn respond(&mut self, c: &BigUint) -> Result<BigUint, ZKPError> {
+            self.wrong.respond(c)
+        }
+    }
+
+    #[test]
+    fn test_mock_transport() {
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let mut prover = ZKPProver::new(zkp.clone(), Secret::new(x.clone()));
+
+        let mut random = zkp.clone();
+        assert!(run(&mut prover, &mut random));
+        let mut constant = Verifier::new(zkp.clone(), |_: &Commitment| BigUint::from(4u32));
+        assert!(run(&mut prover, &mut constant));
+
+        let mut cheater = CheatingProver {
+            honest: ZKPProver::new(zkp.clone(), Secret::new(x)),
+            wrong: ZKPProver::new(zkp.clone(), Secret::new(ZKP::generate_rand_below(zkp.q()))),
+        };
+        assert!(!run(&mut cheater, &mut random));
+    }
+
+    #[test]
+    fn test_respond_uses_up_the_nonce() {
+        let mut prover = ZKPProver::new(ZKP::toy(), Secret::new(BigUint::from(6u32)));
+        assert_eq!(prover.commitment(), Commitment{y1: BigUint::from(2u32), y2: BigUint::from(3u32)});
+
+        let c = BigUint::from(4u32);
+        assert!(prover.respond(&c).is_err());
+        prover.commit();
+        assert!(prover.respond(&c).is_ok());
+        assert!(prover.respond(&c).is_err());
+    }
+}
@@ -0,0 +1,99 @@
+use std::fmt;
+use std::ops::Deref;
+
+use num_bigint::BigUint;
+use zeroize::{Zeroize, ZeroizeOnDrop};
+
+/// Secret material such as the witness x or the nonce k, overwritten with
+/// zeros when dropped
+///
+/// Derefs to `BigUint` so `&secret` can be passed straight to `ZKP::solve`,
+/// `ZKP::prove` and the other proving helpers.
+///
+/// The nonce k must never be reused across proofs: two answers
+/// s1 = k - c1*x and s2 = k - c2*x reveal x = (s1 - s2) / (c2 - c1) mod q.
+///
+/// Zeroing is best effort, temporaries created by big-int arithmetic on the
+/// value are not covered.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Secret(BigUint);
+
+impl Secret {
+    pub fn new(value: BigUint) -> Self {
+        Secret(value)
+    }
+}
+
+impl From<BigUint> for Secret {
+    fn from(value: BigUint) -> Self {
+        Secret(value)
+    }
+}
+
+impl Deref for Secret {
+    type Target = BigUint;
+
+    fn deref(&self) -> &BigUint {
+        &self.0
+    }
+}
+
+impl Zeroize for Secret {
+    fn zeroize(&mut self) {
+        // assign_from_slice refills the existing limb buffer, overwriting it
+        // with zeros before it gets normalized
+        let digits = self.0.bits().div_ceil(32) as usize;
+        self.0.assign_from_slice(&vec![0u32; digits]);
+    }
+}
+
+impl Drop for Secret {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for Secret {}
+
+impl fmt::Debug for Secret {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Secret(..)")
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZKP;
+
+    #[test]
+    fn test_zeroize() {
+        let mut secret = Secret::new(BigUint::from(u128::MAX));
+        secret.zeroize();
+        assert_eq!(*secret, BigUint::from(0u32));
+
+        // dropping zeroizes too
+        let secret = Secret::from(BigUint::from(42u32));
+        drop(secret);
+    }
+
+    #[test]
+    fn test_debug_does_not_leak() {
+        let secret = Secret::new(BigUint::from(123456u32));
+        assert_eq!(format!("{:?}", secret), "Secret(..)");
+    }
+
+    #[test]
+    fn test_secret_in_solve() {
+        let zkp = ZKP::default_1024();
+
+        let x = Secret::new(ZKP::generate_rand_below(zkp.q()));
+        let k = Secret::new(ZKP::generate_rand_below(zkp.q()));
+        let c = ZKP::generate_rand_below(zkp.q());
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+        assert_eq!(s, zkp.solve(&k, &c, &x).unwrap());
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+}
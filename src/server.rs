@@ -1,37 +1,136 @@
-pub mod zkp_auth {
-    include!("./zkp_auth.rs");
-}
+use std::error::Error;
+use std::net::SocketAddr;
+use std::path::PathBuf;
+use std::sync::Arc;
 
-use tonic::{transport::Server, Request, Response, Status};
-use zkp_auth::{auth_server::{Auth, AuthServer}, AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest, AuthenticationChallengeResponse, RegisterRequest, RegisterResponse};
+use clap::{Parser, ValueEnum};
+use zkp_chaum_pedersen::config::ServerConfig;
+use zkp_chaum_pedersen::service::serve_with_shutdown;
+use zkp_chaum_pedersen::ZKPError;
 
-#[derive(Debug, Default)]
-struct AuthImpl {}
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum Group {
+    /// RFC 5114 1024-bit MODP group
+    #[value(name = "1024", alias = "rfc5114-1024")]
+    Rfc5114_1024,
+    /// RFC 5114 2048-bit MODP group
+    #[value(name = "2048", alias = "rfc5114-2048")]
+    Rfc5114_2048,
+}
 
-#[tonic::async_trait]
-impl Auth for AuthImpl {
-    async fn register(&self, request: Request<RegisterRequest>) ->  Result<Response<RegisterResponse>, Status> {
-        todo!()
+impl Group {
+    /// Name in `ZKP::GROUP_NAMES`
+    fn name(self) -> &'static str {
+        match self {
+            Group::Rfc5114_1024 => "rfc5114-1024",
+            Group::Rfc5114_2048 => "rfc5114-2048",
+        }
     }
+}
 
-    async fn create_authentication_challenge(&self, request: Request<AuthenticationChallengeRequest>) ->  Result<Response<AuthenticationChallengeResponse>, Status> {
-        todo!()
-    }
+/// Settings are taken from, highest priority first: these flags, the ZKP_*
+/// environment variables, the --config file and the `ServerConfig` defaults
+#[derive(Debug, Parser)]
+struct Args {
+    /// JSON config file, see `ServerConfig`
+    #[arg(long)]
+    config: Option<PathBuf>,
+
+    /// Address to listen on [default: 127.0.0.1:50051]
+    #[arg(long)]
+    addr: Option<SocketAddr>,
+
+    /// Group the proofs are verified in [default: 1024]
+    #[arg(long, value_enum)]
+    group: Option<Group>,
+}
 
-    async fn verify_authentication(&self, request: Request<AuthenticationAnswerRequest>) ->  Result<Response<AuthenticationAnswerResponse>, Status> {
-        todo!()
+impl Args {
+    /// `var` looks up the ZKP_* variables, `std::env::var` outside of tests
+    fn server_config(&self, var: impl Fn(&str) -> Option<String>) -> Result<ServerConfig, ZKPError> {
+        let config = match &self.config {
+            Some(path) => ServerConfig::load(path)?,
+            None => ServerConfig::default(),
+        };
+        let mut config = config.with_vars(var)?;
+
+        if let Some(addr) = self.addr {
+            config.addr = addr;
+        }
+        if let Some(group) = self.group {
+            config.group = group.name().to_string();
+        }
+        Ok(config)
     }
 }
 
 #[tokio::main]
-async fn main() {
-    let addr = String::from("127.0.0.1:50051");
-    println!("Running the server on {}", addr);
-    let auth_impl = AuthImpl::default();
-
-    Server::builder()
-        .add_service(AuthServer::new(auth_impl))
-        .serve(addr.parse().expect("invalid address"))
-        .await
-        .unwrap();
+async fn main() -> Result<(), Box<dyn Error>> {
+    tracing_subscriber::fmt::init();
+
+    let config = Args::parse().server_config(|key| std::env::var(key).ok())?;
+    tracing::info!("Running the server on {} with the {} group", config.addr, config.group);
+    let auth_impl = Arc::new(config.auth_impl()?);
+
+    let ctrl_c = async {
+        if let Err(e) = tokio::signal::ctrl_c().await {
+            eprintln!("could not listen for ctrl-c: {}", e);
+        }
+    };
+    serve_with_shutdown(auth_impl, config.addr, ctrl_c).await?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zkp_chaum_pedersen::ZKP;
+
+    #[test]
+    fn test_default_args() {
+        let args = Args::try_parse_from(["server"]).unwrap();
+        assert_eq!(args.addr, None);
+        assert_eq!(args.group, None);
+
+        let config = args.server_config(|_| None).unwrap();
+        assert_eq!(config.addr, "127.0.0.1:50051".parse().unwrap());
+        assert_eq!(config.group, "rfc5114-1024");
+    }
+
+    #[test]
+    fn test_group_2048() {
+        let args = Args::try_parse_from(["server", "--addr", "0.0.0.0:6000", "--group", "2048"]).unwrap();
+        assert_eq!(args.addr, Some("0.0.0.0:6000".parse().unwrap()));
+        assert_eq!(ZKP::from_named_group(args.group.unwrap().name()).unwrap().p(), ZKP::default_2048().p());
+
+        let args = Args::try_parse_from(["server", "--group", "rfc5114-2048"]).unwrap();
+        assert_eq!(args.group, Some(Group::Rfc5114_2048));
+
+        assert!(Args::try_parse_from(["server", "--group", "512"]).is_err());
+    }
+
+    #[test]
+    fn test_flags_override_config_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("server.json");
+        std::fs::write(&path, r#"{"addr": "0.0.0.0:6000", "group": "rfc5114-2048", "challenge_ttl_secs": 30}"#).unwrap();
+        let path = path.to_str().unwrap();
+
+        let config = Args::try_parse_from(["server", "--config", path]).unwrap().server_config(|_| None).unwrap();
+        assert_eq!((config.addr, config.group.as_str(), config.challenge_ttl_secs), ("0.0.0.0:6000".parse().unwrap(), "rfc5114-2048", 30));
+
+        let config = Args::try_parse_from(["server", "--config", path, "--group", "1024"]).unwrap().server_config(|_| None).unwrap();
+        assert_eq!((config.addr, config.group.as_str()), ("0.0.0.0:6000".parse().unwrap(), "rfc5114-1024"));
+
+        // the environment overrides the file, the flags override both
+        let env = |key: &str| (key == "ZKP_GROUP").then(|| "rfc5114-1024".to_string());
+        let config = Args::try_parse_from(["server", "--config", path]).unwrap().server_config(env).unwrap();
+        assert_eq!(config.group, "rfc5114-1024");
+        let config = Args::try_parse_from(["server", "--config", path, "--group", "2048"]).unwrap().server_config(env).unwrap();
+        assert_eq!(config.group, "rfc5114-2048");
+
+        let args = Args::try_parse_from(["server", "--config", "/nonexistent/server.json"]).unwrap();
+        assert!(matches!(args.server_config(|_| None), Err(ZKPError::Storage(_))));
+    }
 }
@@ -0,0 +1,1202 @@
+use std::collections::HashMap;
+use std::future::Future;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
+
+use num_bigint::BigUint;
+use rand::{distributions::Alphanumeric, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+use tonic::{metadata::{MetadataMap, MetadataValue}, transport::{server::Router, Server}, Request, Response, Status};
+use tracing::{info, instrument, warn};
+use zeroize::Zeroizing;
+
+use crate::metrics::{Metrics, MetricsSnapshot};
+use crate::proof::{biguint_hex, biguint_hex_option};
+use crate::session::{RegistrationSigner, Session, SessionSigner, DEFAULT_SESSION_TTL};
+use crate::store::{InMemoryUserStore, SessionStore, UserRegistration, UserStore};
+use crate::{VerifyOutcome, ZKPError, ZKP};
+use crate::zkp_auth::{self, auth_server::{Auth, AuthServer}, AuthenticationAnswerRequest, AuthenticationAnswerResponse, AuthenticationChallengeRequest, AuthenticationChallengeResponse, RegisterRequest, RegisterResponse};
+
+/// Commitments r1, r2 and challenge c issued for one authentication attempt
+/// r1 = alpha^k mod p
+/// r2 = beta^k mod p
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Challenge {
+    pub user: String,
+    pub r1: BigUint,
+    pub r2: BigUint,
+    /// `None` for a challenge derived from the challenge key, which is
+    /// recomputed from the auth id and `issued_at_ms` instead, see
+    /// `AuthImpl::with_keyed_challenges`
+    pub c: Option<BigUint>,
+    /// Unix time in milliseconds at issue
+    pub issued_at_ms: u64,
+    /// q of the group c was drawn in, during a migration possibly the legacy one
+    pub q: BigUint,
+    pub created_at: Instant,
+}
+
+/// Snapshot of an `AuthImpl` for moving the server to another host, see
+/// `AuthImpl::export_state`. Holds only what the server stores anyway: public
+/// y1, y2 per user and the open challenges, never x or k
+#[derive(Debug, Clone, PartialEq, Eq, Default, Serialize, Deserialize)]
+pub struct ServerState {
+    pub users: HashMap<String, UserRegistration>,
+    pub challenges: Vec<ChallengeState>,
+}
+
+/// `Challenge` with its age instead of the process local `Instant`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChallengeState {
+    pub auth_id: String,
+    pub user: String,
+    #[serde(with = "biguint_hex")]
+    pub r1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r2: BigUint,
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "biguint_hex_option")]
+    pub c: Option<BigUint>,
+    #[serde(default)]
+    pub issued_at_ms: u64,
+    /// missing in older exports, which are read as issued in the current
+    /// challenge group
+    #[serde(default, skip_serializing_if = "Option::is_none", with = "biguint_hex_option")]
+    pub q: Option<BigUint>,
+    pub age_ms: u64,
+}
+
+/// How long a challenge can be answered unless set with `with_challenge_ttl`
+pub const DEFAULT_CHALLENGE_TTL: Duration = Duration::from_secs(120);
+
+/// Failed verifications allowed per user and window unless set with `with_rate_limit`
+pub const DEFAULT_MAX_FAILED_ATTEMPTS: u32 = 5;
+pub const DEFAULT_RATE_LIMIT_WINDOW: Duration = Duration::from_secs(60);
+
+/// Bytes of the salt drawn for every user at registration, see `UserRegistration::salt`
+pub const USER_SALT_LEN: usize = 16;
+
+/// Fixed-window counter of failed verifications per user
+#[derive(Debug)]
+struct RateLimiter {
+    max_failures: u32,
+    window: Duration,
+    /// user -> (window start, failures in the window)
+    failures: HashMap<String, (Instant, u32)>,
+}
+
+impl RateLimiter {
+    fn new(max_failures: u32, window: Duration) -> Self {
+        RateLimiter {
+            max_failures,
+            window,
+            failures: HashMap::new(),
+        }
+    }
+
+    fn is_limited(&mut self, user: &str) -> bool {
+        match self.failures.get(user) {
+            Some((start, _)) if start.elapsed() > self.window => {
+                self.failures.remove(user);
+                false
+            }
+            Some((_, count)) => *count >= self.max_failures,
+            None => false,
+        }
+    }
+
+    fn record_failure(&mut self, user: &str) {
+        let entry = self.failures.entry(user.to_string()).or_insert((Instant::now(), 0));
+        if entry.0.elapsed() > self.window {
+            *entry = (Instant::now(), 0);
+        }
+        entry.1 += 1;
+    }
+
+    fn reset(&mut self, user: &str) {
+        self.failures.remove(user);
+    }
+}
+
+/// Locking: each piece of state has its own std lock and every guard is dropped
+/// before the next `.await`. The user store sits behind an `RwLock` since it is
+/// read on every challenge and verification but only written by `register`,
+/// `import_state` and `flush`, concurrent logins then only contend on the
+/// short `challenges` and `rate_limiter` critical sections
+pub struct AuthImpl {
+    /// shared with the blocking tasks running `verify`
+    zkp: Arc<ZKP>,
+    users: RwLock<Box<dyn UserStore>>,
+    /// keyed by auth_id
+    challenges: Mutex<HashMap<String, Challenge>>,
+    challenge_ttl: Duration,
+    rate_limiter: Mutex<RateLimiter>,
+    sessions: SessionSigner,
+    /// set in stateless mode, see `with_registration_tokens`
+    registrations: Option<RegistrationSigner>,
+    /// group still accepted next to `zkp` until the instant, see `with_legacy_zkp`
+    legacy: Option<(Arc<ZKP>, Instant)>,
+    /// set when challenges are derived instead of drawn, see `with_keyed_challenges`
+    challenge_key: Option<Zeroizing<Vec<u8>>>,
+    /// sessions issued by `verify_authentication`, see `with_session_store`
+    session_store: Option<RwLock<Box<dyn SessionStore>>>,
+    /// `verify` on the request's worker thread, see `with_inline_verification`
+    inline_verification: bool,
+    metrics: Metrics,
+}
+
+impl Default for AuthImpl {
+    fn default() -> Self {
+        AuthImpl::with_user_store(Box::new(InMemoryUserStore::default()))
+    }
+}
+
+impl AuthImpl {
+    /// Verifies proofs in `zkp` and keeps registrations in `store`, every other
+    /// setting at its default
+    pub fn new(zkp: ZKP, store: Box<dyn UserStore>) -> Self {
+        AuthImpl {
+            zkp: Arc::new(zkp),
+            users: RwLock::new(store),
+            challenges: Mutex::new(HashMap::new()),
+            challenge_ttl: DEFAULT_CHALLENGE_TTL,
+            rate_limiter: Mutex::new(RateLimiter::new(DEFAULT_MAX_FAILED_ATTEMPTS, DEFAULT_RATE_LIMIT_WINDOW)),
+            sessions: SessionSigner::random(DEFAULT_SESSION_TTL),
+            registrations: None,
+            legacy: None,
+            challenge_key: None,
+            session_store: None,
+            inline_verification: false,
+            metrics: Metrics::default(),
+        }
+    }
+
+    /// Uses the 1024-bit group and keeps registrations in `store`
+    pub fn with_user_store(store: Box<dyn UserStore>) -> Self {
+        AuthImpl::new(ZKP::default_1024(), store)
+    }
+
+    /// Verifies proofs in `zkp` instead of the 1024-bit group
+    pub fn with_zkp(mut self, zkp: ZKP) -> Self {
+        self.zkp = Arc::new(zkp);
+        self
+    }
+
+    /// Challenges older than `challenge_ttl` are rejected with `DeadlineExceeded`
+    pub fn with_challenge_ttl(mut self, challenge_ttl: Duration) -> Self {
+        self.challenge_ttl = challenge_ttl;
+        self
+    }
+
+    /// After `max_failures` failed verifications for a user within `window`,
+    /// further answers for that user get `ResourceExhausted` until the window ends
+    pub fn with_rate_limit(mut self, max_failures: u32, window: Duration) -> Self {
+        self.rate_limiter = Mutex::new(RateLimiter::new(max_failures, window));
+        self
+    }
+
+    /// Signs session tokens with `key` instead of a random per-process key, so
+    /// tokens survive restarts and other services can validate them
+    pub fn with_session_key(mut self, key: Vec<u8>) -> Self {
+        self.sessions = SessionSigner::new(key, self.sessions.ttl());
+        self
+    }
+
+    /// Session tokens issued by `verify_authentication` expire after `session_ttl`
+    pub fn with_session_ttl(mut self, session_ttl: Duration) -> Self {
+        self.sessions = self.sessions.with_ttl(session_ttl);
+        self
+    }
+
+    /// Stateless mode: `register` stores nothing and instead returns a registration
+    /// token holding the user's y1, y2 and salt signed with `key`. The client sends
+    /// it back as `REGISTRATION_TOKEN_KEY` metadata on `create_authentication_challenge`
+    /// and `verify_authentication`, which take y1, y2 and the salt from the token
+    /// instead of the user store
+    ///
+    /// The token is only as trustworthy as `key`: it must be issued by this server
+    /// (or one sharing the key) and the key must never reach clients, since anyone
+    /// who can sign a token can pick a y1, y2 they know x for and log in as any
+    /// user. The server also no longer sees earlier registrations, so a user name
+    /// can be registered again with other commitments and tokens cannot be revoked
+    /// without changing the key
+    pub fn with_registration_tokens(mut self, key: Vec<u8>) -> Self {
+        self.registrations = Some(RegistrationSigner::new(key));
+        self
+    }
+
+    /// Migration to `zkp` from an older group: for `window` from now, proofs that
+    /// fail in `zkp` are tried again in `legacy`, and the group that verified is
+    /// logged. Once the window ends only `zkp` is accepted, so users registered
+    /// with `legacy` commitments have to register again before then
+    ///
+    /// During the window challenges are drawn below the smaller of the two q, so
+    /// clients of either group can answer them. Elements and scalars are decoded
+    /// with the widths of `zkp`, which must not be smaller than those of `legacy`
+    pub fn with_legacy_zkp(mut self, legacy: ZKP, window: Duration) -> Self {
+        self.legacy = Some((Arc::new(legacy), Instant::now() + window));
+        self
+    }
+
+    /// Every session issued is also kept in `store`, and `validate_session` only
+    /// accepts tokens whose session is still stored there, so `revoke_session`
+    /// ends a session before its token expires
+    pub fn with_session_store(mut self, store: Box<dyn SessionStore>) -> Self {
+        self.session_store = Some(RwLock::new(store));
+        self
+    }
+
+    /// Challenges are derived as `ZKP::keyed_challenge(key, auth_id, salt, t)` with
+    /// the user's salt and t the unix time in milliseconds at issue, instead of
+    /// drawn at random. Only
+    /// t and the q of the group c was drawn in are stored with the challenge and
+    /// c is recomputed when the answer arrives, so any server holding `key` can
+    /// take over exported challenges
+    ///
+    /// Only as safe as `key`: unlike random challenges, a leaked key lets a
+    /// client compute its challenge in advance and answer it without x, see
+    /// `ZKP::keyed_challenge`. Derived challenges are not bound to r1, r2 the
+    /// way drawn ones are
+    pub fn with_keyed_challenges(mut self, key: Vec<u8>) -> Self {
+        self.challenge_key = Some(Zeroizing::new(key));
+        self
+    }
+
+    /// Runs `verify` on the worker thread handling the request instead of the
+    /// blocking pool, ignoring the client deadline. Only the baseline
+    /// tests/loadtest.rs compares the blocking pool against
+    #[cfg(feature = "loadtest")]
+    pub fn with_inline_verification(mut self) -> Self {
+        self.inline_verification = true;
+        self
+    }
+
+    /// Group challenges are drawn from: during a migration the one with the
+    /// smaller q, so clients of either group can answer them
+    fn challenge_zkp(&self) -> &ZKP {
+        match self.legacy_zkp() {
+            Some(legacy) if legacy.q() < self.zkp.q() => legacy,
+            _ => &self.zkp,
+        }
+    }
+
+    /// c of `challenge`, stored or recomputed from the challenge key and the
+    /// salt of the user answering, in the group it was issued in. That is the
+    /// legacy group even after its window closed, so a challenge issued just
+    /// before still gets the same c
+    fn challenge_c(&self, auth_id: &str, challenge: &Challenge, salt: &[u8]) -> Result<BigUint, ZKPError> {
+        let key = match (&challenge.c, &self.challenge_key) {
+            (Some(c), _) => return Ok(c.clone()),
+            (None, Some(key)) => key,
+            (None, None) => return Err(ZKPError::Storage(format!("challenge {} has no c and no challenge key is set", auth_id))),
+        };
+
+        let legacy = self.legacy.as_ref().map(|(legacy, _)| legacy.as_ref());
+        let zkp = [Some(self.zkp.as_ref()), legacy]
+            .into_iter()
+            .flatten()
+            .find(|zkp| *zkp.q() == challenge.q)
+            .ok_or_else(|| ZKPError::Storage(format!("challenge {} was issued in a group this server does not have", auth_id)))?;
+        Ok(zkp.keyed_challenge(key, auth_id, salt, challenge.issued_at_ms))
+    }
+
+    /// The legacy group while its migration window is open
+    fn legacy_zkp(&self) -> Option<&Arc<ZKP>> {
+        self.legacy.as_ref().filter(|(_, until)| Instant::now() < *until).map(|(legacy, _)| legacy)
+    }
+
+    /// User name in a session token returned by `verify_authentication`. With a
+    /// session store the session must also still be stored
+    pub fn validate_session(&self, token: &str) -> Result<String, ZKPError> {
+        let (user, session_id) = self.sessions.validate_with_id(token)?;
+        if let Some(store) = &self.session_store {
+            let store = store.read().map_err(|_| ZKPError::Storage("session store poisoned".to_string()))?;
+            store.validate(&session_id, SystemTime::now())?;
+        }
+        Ok(user)
+    }
+
+    /// Ends the session of `token`, output = whether it was still stored.
+    /// Needs a session store, without one tokens stay valid until they expire
+    pub fn revoke_session(&self, token: &str) -> Result<bool, ZKPError> {
+        let store = self.session_store.as_ref().ok_or_else(|| ZKPError::InvalidParameter("revoking sessions needs a session store".to_string()))?;
+        let (_, session_id) = self.sessions.validate_with_id(token)?;
+        let mut store = store.write().map_err(|_| ZKPError::Storage("session store poisoned".to_string()))?;
+        store.revoke(&session_id)
+    }
+
+    /// Diagnostics: whether `s` would answer the challenge under `auth_id`. The
+    /// challenge stays open, no session is issued and neither the rate limiter
+    /// nor the metrics see the attempt
+    ///
+    /// Admin only. Anyone who can call it can test guesses of s against an open
+    /// challenge without limit, so it must never be exposed through the Auth
+    /// service or to clients. Needs a user store, stateless mode has no y1, y2
+    /// to check against without the client's registration token
+    pub fn dry_run_verify(&self, auth_id: &str, s: &BigUint) -> Result<VerifyOutcome, ZKPError> {
+        if self.registrations.is_some() {
+            return Err(ZKPError::InvalidParameter("dry runs need a user store, not registration tokens".to_string()));
+        }
+
+        let challenge = {
+            let challenges = self.challenges.lock().map_err(|_| ZKPError::Storage("challenge store poisoned".to_string()))?;
+            challenges.get(auth_id).cloned().ok_or_else(|| ZKPError::ChallengeNotFound(auth_id.to_string()))?
+        };
+        if challenge.created_at.elapsed() > self.challenge_ttl {
+            return Err(ZKPError::ChallengeExpired(auth_id.to_string()));
+        }
+
+        let users = self.users.read().map_err(|_| ZKPError::Storage("user store poisoned".to_string()))?;
+        let registration = users.get(&challenge.user).ok_or_else(|| ZKPError::UserNotFound(challenge.user.clone()))?;
+        drop(users);
+
+        let c = &self.challenge_c(auth_id, &challenge, &registration.salt)?;
+        let (r1, r2, y1, y2) = (&challenge.r1, &challenge.r2, &registration.y1, &registration.y2);
+        let outcome = self.zkp.verify_detailed(r1, r2, y1, y2, c, s);
+        if outcome != VerifyOutcome::Ok && self.legacy_zkp().is_some_and(|legacy| legacy.verify(r1, r2, y1, y2, c, s)) {
+            return Ok(VerifyOutcome::Ok);
+        }
+        Ok(outcome)
+    }
+
+    /// Group parameters used to verify the proofs
+    pub fn zkp(&self) -> &ZKP {
+        &self.zkp
+    }
+
+    /// Counters since the server started, `to_string` gives the Prometheus text format
+    pub fn metrics(&self) -> MetricsSnapshot {
+        self.metrics.snapshot()
+    }
+
+    /// Every registered user and every challenge that has not expired yet
+    pub fn export_state(&self) -> Result<ServerState, ZKPError> {
+        let users = self.users.read().map_err(|_| ZKPError::Storage("user store poisoned".to_string()))?;
+        let users = users.users().into_iter().collect();
+
+        let challenges = self.challenges.lock().map_err(|_| ZKPError::Storage("challenge store poisoned".to_string()))?;
+        let challenges = challenges
+            .iter()
+            .filter(|(_, challenge)| challenge.created_at.elapsed() <= self.challenge_ttl)
+            .map(|(auth_id, challenge)| ChallengeState {
+                auth_id: auth_id.clone(),
+                user: challenge.user.clone(),
+                r1: challenge.r1.clone(),
+                r2: challenge.r2.clone(),
+                c: challenge.c.clone(),
+                issued_at_ms: challenge.issued_at_ms,
+                q: Some(challenge.q.clone()),
+                age_ms: challenge.created_at.elapsed().as_millis() as u64,
+            })
+            .collect();
+
+        Ok(ServerState{users, challenges})
+    }
+
+    /// Adds the users and challenges of `state`, replacing entries with the same
+    /// user name or auth id. Challenges keep the age they had at export
+    pub fn import_state(&mut self, state: ServerState) -> Result<(), ZKPError> {
+        let users = self.users.get_mut().map_err(|_| ZKPError::Storage("user store poisoned".to_string()))?;
+        for (user, registration) in state.users {
+            users.insert(user, registration)?;
+        }
+
+        let default_q = self.challenge_zkp().q().clone();
+        let challenges = self.challenges.get_mut().map_err(|_| ZKPError::Storage("challenge store poisoned".to_string()))?;
+        let now = Instant::now();
+        for challenge in state.challenges {
+            let created_at = now.checked_sub(Duration::from_millis(challenge.age_ms)).unwrap_or(now);
+            challenges.insert(challenge.auth_id, Challenge {
+                user: challenge.user,
+                r1: challenge.r1,
+                r2: challenge.r2,
+                c: challenge.c,
+                issued_at_ms: challenge.issued_at_ms,
+                q: challenge.q.unwrap_or_else(|| default_q.clone()),
+                created_at,
+            });
+        }
+        Ok(())
+    }
+
+    /// Flushes the user store, e.g. before the process exits
+    pub fn flush(&self) -> Result<(), ZKPError> {
+        let mut users = self.users.write().map_err(|_| ZKPError::Storage("user store poisoned".to_string()))?;
+        users.flush()
+    }
+}
+
+/// The Auth service next to `grpc.health.v1.Health`, which reports Auth as
+/// SERVING, and server reflection for tools like grpcurl
+pub async fn router(auth_impl: Arc<AuthImpl>) -> Router {
+    let (mut health_reporter, health_service) = tonic_health::server::health_reporter();
+    health_reporter.set_serving::<AuthServer<AuthImpl>>().await;
+
+    let reflection_service = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(zkp_auth::FILE_DESCRIPTOR_SET)
+        .build_v1()
+        .expect("descriptor set is generated by build.rs");
+
+    Server::builder()
+        .add_service(health_service)
+        .add_service(reflection_service)
+        .add_service(AuthServer::from_arc(auth_impl))
+}
+
+/// Serves `router(auth_impl)` on `addr` until `signal` resolves, then flushes the user store
+pub async fn serve_with_shutdown<F: Future<Output = ()>>(auth_impl: Arc<AuthImpl>, addr: SocketAddr, signal: F) -> Result<(), ZKPError> {
+    router(auth_impl.clone())
+        .await
+        .serve_with_shutdown(addr, async {
+            signal.await;
+            info!("Shutting down the server");
+        })
+        .await
+        .map_err(|e| ZKPError::Transport(e.to_string()))?;
+
+    auth_impl.flush()
+}
+
+/// Metadata key for the registration token on `create_authentication_challenge`
+/// and `verify_authentication` requests in stateless mode, see `AuthImpl::with_registration_tokens`
+pub const REGISTRATION_TOKEN_KEY: &str = "zkp-registration-token";
+/// Metadata key holding `ZKPError::code` on error statuses
+pub const ERROR_CODE_KEY: &str = "zkp-error-code";
+/// Metadata key holding `ZKPError::is_retryable` ("true" or "false") on error statuses
+pub const RETRYABLE_KEY: &str = "zkp-retryable";
+
+/// Metadata key of the gRPC deadline, see `request_deadline`
+const GRPC_TIMEOUT_KEY: &str = "grpc-timeout";
+
+impl From<ZKPError> for Status {
+    fn from(err: ZKPError) -> Self {
+        let mut status = match err {
+            ZKPError::InvalidParameter(_) | ZKPError::WeakParameters(_) | ZKPError::InconsistentParameters(_) | ZKPError::Decode(_) | ZKPError::NonceReused => {
+                Status::invalid_argument(err.to_string())
+            }
+            ZKPError::UserNotFound(_) | ZKPError::ChallengeNotFound(_) => Status::not_found(err.to_string()),
+            ZKPError::UserAlreadyExists(_) => Status::already_exists(err.to_string()),
+            ZKPError::ChallengeExpired(_) | ZKPError::Timeout(_) => Status::deadline_exceeded(err.to_string()),
+            ZKPError::InvalidSession(_) => Status::unauthenticated(err.to_string()),
+            ZKPError::RateLimited(_) => Status::resource_exhausted(err.to_string()),
+            ZKPError::VerificationFailed => Status::permission_denied(err.to_string()),
+            ZKPError::Transport(_) => Status::unavailable(err.to_string()),
+            ZKPError::Storage(_) => Status::internal(err.to_string()),
+        };
+
+        let metadata = status.metadata_mut();
+        metadata.insert(ERROR_CODE_KEY, MetadataValue::from_static(err.code()));
+        metadata.insert(RETRYABLE_KEY, MetadataValue::from_static(if err.is_retryable() { "true" } else { "false" }));
+        status
+    }
+}
+
+/// `ZKPError::code` carried by a status returned from this service
+pub fn error_code(status: &Status) -> Option<&str> {
+    status.metadata().get(ERROR_CODE_KEY).and_then(|value| value.to_str().ok())
+}
+
+/// `ZKPError::is_retryable` carried by a status returned from this service
+pub fn is_retryable(status: &Status) -> bool {
+    status.metadata().get(RETRYABLE_KEY).is_some_and(|value| value == "true")
+}
+
+/// Deadline from the `grpc-timeout` header a tonic client sets with
+/// `Request::set_timeout`, counted from now. A missing or malformed header
+/// means no deadline
+///
+/// The value is up to 8 digits followed by a unit: H, M, S, m, u or n
+fn request_deadline(metadata: &MetadataMap) -> Option<Instant> {
+    let value = metadata.get(GRPC_TIMEOUT_KEY)?.to_str().ok()?;
+    let (amount, unit) = value.split_at(value.len().checked_sub(1)?);
+    if amount.is_empty() || amount.len() > 8 {
+        return None;
+    }
+    let amount: u64 = amount.parse().ok()?;
+    let timeout = match unit {
+        "H" => Duration::from_secs(amount * 3600),
+        "M" => Duration::from_secs(amount * 60),
+        "S" => Duration::from_secs(amount),
+        "m" => Duration::from_millis(amount),
+        "u" => Duration::from_micros(amount),
+        "n" => Duration::from_nanos(amount),
+        _ => return None,
+    };
+    Instant::now().checked_add(timeout)
+}
+
+/// Runs `f` on the blocking pool, `Timeout` for `operation` if `deadline`
+/// passes first. A blocking task cannot be cancelled, its result is dropped
+async fn blocking_until<T: Send + 'static>(deadline: Option<Instant>, operation: &str, f: impl FnOnce() -> T + Send + 'static) -> Result<T, Status> {
+    let timeout = || Status::from(ZKPError::Timeout(operation.to_string()));
+    let result = match deadline {
+        Some(deadline) if deadline <= Instant::now() => return Err(timeout()),
+        Some(deadline) => tokio::time::timeout_at(deadline.into(), tokio::task::spawn_blocking(f)).await.map_err(|_| timeout())?,
+        None => tokio::task::spawn_blocking(f).await,
+    };
+    result.map_err(|_| Status::internal(format!("{} task failed", operation)))
+}
+
+/// Registration of `user` in the token sent as `REGISTRATION_TOKEN_KEY`
+fn token_registration(registrations: &RegistrationSigner, token: Option<String>, user: &str) -> Result<UserRegistration, ZKPError> {
+    let token = token.ok_or_else(|| ZKPError::InvalidSession("missing registration token".to_string()))?;
+    let (token_user, registration) = registrations.validate(&token)?;
+    if token_user != user {
+        warn!(user, "registration token is for another user");
+        return Err(ZKPError::InvalidSession("registration token is for another user".to_string()));
+    }
+    Ok(registration)
+}
+
+/// Drawn challenge for `registration`: its salt and the fresh `nonce` are
+/// hashed ahead of the commitments, so the same r1, r2 give different
+/// challenges for every user and every attempt
+fn salted_challenge(zkp: &ZKP, registration: &UserRegistration, nonce: &[u8], r1: &BigUint, r2: &BigUint) -> BigUint {
+    let salt = [registration.salt.as_slice(), nonce].concat();
+    zkp.challenge_bound_with_salt(&salt, r1, r2, &registration.y1, &registration.y2)
+}
+
+fn unix_time_ms() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_millis() as u64).unwrap_or(0)
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    rand::thread_rng().fill_bytes(&mut bytes);
+    bytes
+}
+
+fn generate_random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .map(char::from)
+        .collect()
+}
+
+#[tonic::async_trait]
+impl Auth for AuthImpl {
+    /// Registering a user name that is already taken returns `AlreadyExists`,
+    /// the existing registration is never overwritten. In stateless mode nothing
+    /// is stored and the response carries the registration token
+    ///
+    /// y1 and y2 outside the order q subgroup get `InvalidArgument`, see
+    /// `ZKP::is_subgroup_element`
+    #[instrument(skip_all, fields(user = %request.get_ref().user))]
+    async fn register(&self, request: Request<RegisterRequest>) ->  Result<Response<RegisterResponse>, Status> {
+        let request = request.into_inner();
+
+        let registration = UserRegistration {
+            y1: self.zkp.element_from_bytes(&request.y1)?,
+            y2: self.zkp.element_from_bytes(&request.y2)?,
+            salt: random_bytes(USER_SALT_LEN),
+        };
+        // keys outside the order q subgroup are rejected before anything is stored
+        // or signed, during a migration keys of the legacy group are accepted too
+        for (name, y) in [("y1", &registration.y1), ("y2", &registration.y2)] {
+            if !self.zkp.is_subgroup_element(y) && !self.legacy_zkp().is_some_and(|legacy| legacy.is_subgroup_element(y)) {
+                warn!("{} is not in the order q subgroup", name);
+                return Err(ZKPError::InvalidParameter(format!("{} is not in the order q subgroup", name)).into());
+            }
+        }
+
+        if let Some(registrations) = &self.registrations {
+            let registration_token = registrations.issue(&request.user, &registration);
+            self.metrics.registered();
+            info!("registration token issued");
+            return Ok(Response::new(RegisterResponse { registration_token }));
+        }
+
+        let mut users = self.users.write().map_err(|_| Status::internal("user store poisoned"))?;
+        if users.get(&request.user).is_some() {
+            warn!("user already registered");
+            return Err(ZKPError::UserAlreadyExists(request.user).into());
+        }
+        users.insert(request.user, registration)?;
+        self.metrics.registered();
+        info!("user registered");
+
+        Ok(Response::new(RegisterResponse { registration_token: String::new() }))
+    }
+
+    /// In stateless mode the request must carry the user's registration token,
+    /// the challenge is bound to the salt in it
+    #[instrument(skip_all, fields(user = %request.get_ref().user))]
+    async fn create_authentication_challenge(&self, request: Request<AuthenticationChallengeRequest>) ->  Result<Response<AuthenticationChallengeResponse>, Status> {
+        let registration_token = request.metadata().get(REGISTRATION_TOKEN_KEY).map(|value| value.to_str().unwrap_or_default().to_string());
+        let request = request.into_inner();
+
+        let registration = match &self.registrations {
+            Some(registrations) => token_registration(registrations, registration_token, &request.user)?,
+            None => {
+                let users = self.users.read().map_err(|_| Status::internal("user store poisoned"))?;
+                users.get(&request.user).ok_or_else(|| {
+                    warn!("challenge requested for unknown user");
+                    ZKPError::UserNotFound(request.user.clone())
+                })?
+            }
+        };
+
+        let r1 = self.zkp.element_from_bytes(&request.r1)?;
+        let r2 = self.zkp.element_from_bytes(&request.r2)?;
+
+        let challenge_zkp = self.challenge_zkp();
+        let auth_id = generate_random_string(32);
+        let issued_at_ms = unix_time_ms();
+
+        // both kinds of challenge are bound to the user's salt. Keyed ones are
+        // not stored, `challenge_c` recomputes them with the salt of the answer
+        let (c, stored_c) = match &self.challenge_key {
+            Some(key) => (challenge_zkp.keyed_challenge(key, &auth_id, &registration.salt, issued_at_ms), None),
+            None => {
+                let c = salted_challenge(challenge_zkp, &registration, &random_bytes(32), &r1, &r2);
+                (c.clone(), Some(c))
+            }
+        };
+
+        let challenge = Challenge {
+            user: request.user,
+            r1,
+            r2,
+            c: stored_c,
+            issued_at_ms,
+            q: challenge_zkp.q().clone(),
+            created_at: Instant::now(),
+        };
+
+        let mut challenges = self.challenges.lock().map_err(|_| Status::internal("challenge store poisoned"))?;
+        challenges.retain(|_, challenge| challenge.created_at.elapsed() <= self.challenge_ttl);
+        challenges.insert(auth_id.clone(), challenge);
+        self.metrics.challenge_issued();
+        info!(%auth_id, "challenge issued");
+
+        Ok(Response::new(AuthenticationChallengeResponse { auth_id, c: c.to_bytes_be() }))
+    }
+
+    /// The user is resolved from the challenge stored under `auth_id`, never from
+    /// the request, so one user can have several challenges open at once
+    ///
+    /// `verify` runs on tokio's blocking pool. Its modpows are CPU bound and
+    /// await nothing, so on a worker thread they would hold up every request
+    /// scheduled on that thread, registrations and challenges included. On a
+    /// single core this does not improve p99 over inline verification, a
+    /// multi-core figure is still missing, see tests/loadtest.rs
+    #[instrument(skip_all, fields(auth_id = %request.get_ref().auth_id))]
+    async fn verify_authentication(&self, request: Request<AuthenticationAnswerRequest>) ->  Result<Response<AuthenticationAnswerResponse>, Status> {
+        let deadline = request_deadline(request.metadata());
+        let registration_token = request.metadata().get(REGISTRATION_TOKEN_KEY).map(|value| value.to_str().unwrap_or_default().to_string());
+        let request = request.into_inner();
+        // checked before the challenge is taken, an oversized s does not use it up
+        let s = self.zkp.scalar_from_bytes(&request.s)?;
+
+        // every challenge can be answered once, a second answer gets NotFound
+        // the guards are scoped to blocks since they must not be held across the await below
+        let challenge = {
+            let mut challenges = self.challenges.lock().map_err(|_| Status::internal("challenge store poisoned"))?;
+            challenges.remove(&request.auth_id).ok_or_else(|| {
+                warn!("unknown auth id");
+                ZKPError::ChallengeNotFound(request.auth_id.clone())
+            })?
+        };
+
+        let user = challenge.user.as_str();
+        if challenge.created_at.elapsed() > self.challenge_ttl {
+            warn!(user, "challenge expired");
+            return Err(ZKPError::ChallengeExpired(request.auth_id).into());
+        }
+
+        {
+            let mut rate_limiter = self.rate_limiter.lock().map_err(|_| Status::internal("rate limiter poisoned"))?;
+            if rate_limiter.is_limited(user) {
+                warn!(user, "too many failed attempts");
+                return Err(ZKPError::RateLimited(challenge.user).into());
+            }
+        }
+
+        let registration = match &self.registrations {
+            Some(registrations) => token_registration(registrations, registration_token, user)?,
+            None => {
+                let users = self.users.read().map_err(|_| Status::internal("user store poisoned"))?;
+                users.get(user).ok_or_else(|| ZKPError::UserNotFound(user.to_string()))?
+            }
+        };
+
+        // four modpows take under a millisecond for 1024-bit p and a few for 2048-bit p,
+        // a failed proof costs four more while a legacy group is accepted.
+        // A client deadline that passes first ends the request with DeadlineExceeded,
+        // the challenge is used up either way
+        let c = self.challenge_c(&request.auth_id, &challenge, &registration.salt)?;
+        let (zkp, legacy) = (self.zkp.clone(), self.legacy_zkp().cloned());
+        let (r1, r2) = (challenge.r1, challenge.r2);
+        let verify = move || {
+            let (y1, y2) = (&registration.y1, &registration.y2);
+            if zkp.verify(&r1, &r2, y1, y2, &c, &s) {
+                Some("primary")
+            } else if legacy.is_some_and(|legacy| legacy.verify(&r1, &r2, y1, y2, &c, &s)) {
+                Some("legacy")
+            } else {
+                None
+            }
+        };
+        let group = if self.inline_verification {
+            Ok(verify())
+        } else {
+            blocking_until(deadline, "verify_authentication", verify).await
+        };
+        let group = group.inspect_err(|status| if status.code() == tonic::Code::DeadlineExceeded {
+            warn!(user, "deadline passed before verification finished");
+        })?;
+        self.metrics.verified(group.is_some());
+
+        let mut rate_limiter = self.rate_limiter.lock().map_err(|_| Status::internal("rate limiter poisoned"))?;
+        let Some(group) = group else {
+            rate_limiter.record_failure(user);
+            warn!(user, "verification failed");
+            return Err(ZKPError::VerificationFailed.into());
+        };
+        rate_limiter.reset(user);
+        drop(rate_limiter);
+
+        info!(user, group, "verification succeeded");
+        let session = Session::new(user, self.sessions.ttl());
+        let session_id = self.sessions.sign(&session);
+        if let Some(store) = &self.session_store {
+            let mut store = store.write().map_err(|_| Status::internal("session store poisoned"))?;
+            store.insert(session)?;
+        }
+        self.metrics.session_issued();
+        Ok(Response::new(AuthenticationAnswerResponse { session_id }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::store::InMemorySessionStore;
+
+    #[test]
+    fn test_error_to_status() {
+        assert_eq!(Status::from(ZKPError::InvalidParameter("q".to_string())).code(), tonic::Code::InvalidArgument);
+        assert_eq!(Status::from(ZKPError::WeakParameters("p".to_string())).code(), tonic::Code::InvalidArgument);
+        assert_eq!(Status::from(ZKPError::InconsistentParameters("q".to_string())).code(), tonic::Code::InvalidArgument);
+        assert_eq!(Status::from(ZKPError::Decode("zz".to_string())).code(), tonic::Code::InvalidArgument);
+        assert_eq!(Status::from(ZKPError::UserNotFound("alice".to_string())).code(), tonic::Code::NotFound);
+        assert_eq!(Status::from(ZKPError::UserAlreadyExists("alice".to_string())).code(), tonic::Code::AlreadyExists);
+        assert_eq!(Status::from(ZKPError::ChallengeNotFound("id".to_string())).code(), tonic::Code::NotFound);
+        assert_eq!(Status::from(ZKPError::ChallengeExpired("id".to_string())).code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(Status::from(ZKPError::VerificationFailed).code(), tonic::Code::PermissionDenied);
+        assert_eq!(Status::from(ZKPError::InvalidSession("expired".to_string())).code(), tonic::Code::Unauthenticated);
+        assert_eq!(Status::from(ZKPError::RateLimited("alice".to_string())).code(), tonic::Code::ResourceExhausted);
+        assert_eq!(Status::from(ZKPError::Transport("down".to_string())).code(), tonic::Code::Unavailable);
+        assert_eq!(Status::from(ZKPError::Timeout("register".to_string())).code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(Status::from(ZKPError::Storage("disk full".to_string())).code(), tonic::Code::Internal);
+
+        let status = Status::from(ZKPError::ChallengeExpired("id".to_string()));
+        assert_eq!(error_code(&status), Some("challenge-expired"));
+        assert!(is_retryable(&status));
+
+        let status = Status::from(ZKPError::VerificationFailed);
+        assert_eq!(error_code(&status), Some("verification-failed"));
+        assert!(!is_retryable(&status));
+
+        assert_eq!(error_code(&Status::internal("other")), None);
+    }
+
+    #[test]
+    fn test_rate_limiter_window() {
+        let mut limiter = RateLimiter::new(2, Duration::from_millis(50));
+        assert!(!limiter.is_limited("alice"));
+
+        limiter.record_failure("alice");
+        assert!(!limiter.is_limited("alice"));
+        limiter.record_failure("alice");
+        assert!(limiter.is_limited("alice"));
+        assert!(!limiter.is_limited("bob"));
+
+        std::thread::sleep(Duration::from_millis(60));
+        assert!(!limiter.is_limited("alice"));
+
+        limiter.record_failure("alice");
+        limiter.reset("alice");
+        assert!(!limiter.is_limited("alice"));
+    }
+
+    /// Collects the level of every event
+    struct LevelRecorder(Arc<Mutex<Vec<tracing::Level>>>);
+
+    impl<S: tracing::Subscriber> tracing_subscriber::Layer<S> for LevelRecorder {
+        fn on_event(&self, event: &tracing::Event<'_>, _ctx: tracing_subscriber::layer::Context<'_, S>) {
+            self.0.lock().unwrap().push(*event.metadata().level());
+        }
+    }
+
+    #[tokio::test]
+    async fn test_verification_failure_emits_warning() {
+        use tracing_subscriber::layer::SubscriberExt;
+
+        let levels = Arc::new(Mutex::new(Vec::new()));
+        let subscriber = tracing_subscriber::registry().with(LevelRecorder(levels.clone()));
+        let _guard = tracing::subscriber::set_default(subscriber);
+
+        let auth_impl = AuthImpl::default();
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2, _) = zkp.prove(&x, &k, &BigUint::from(0u32)).unwrap();
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+        assert!(!levels.lock().unwrap().contains(&tracing::Level::WARN));
+
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: vec![1] };
+        auth_impl.verify_authentication(Request::new(request)).await.unwrap_err();
+        assert!(levels.lock().unwrap().contains(&tracing::Level::WARN));
+    }
+
+    #[tokio::test]
+    async fn test_toy_group_flow() {
+        let auth_impl = AuthImpl::new(ZKP::toy(), Box::new(InMemoryUserStore::default()));
+        assert_eq!(auth_impl.zkp(), &ZKP::toy());
+
+        // x = 6 and k = 7 as in the toy example, y1 = 2, y2 = 3, r1 = 8, r2 = 4
+        let request = RegisterRequest { user: "alice".to_string(), y1: vec![2], y2: vec![3] };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: vec![8], r2: vec![4] };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+        let c = BigUint::from_bytes_be(&response.c);
+        assert!(c > BigUint::from(0u32) && c < BigUint::from(11u32));
+
+        let s = ZKP::toy().solve(&BigUint::from(7u32), &c, &BigUint::from(6u32)).unwrap();
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+        let session_id = auth_impl.verify_authentication(Request::new(request)).await.unwrap().into_inner().session_id;
+        assert_eq!(auth_impl.validate_session(&session_id), Ok("alice".to_string()));
+    }
+
+    #[test]
+    fn test_request_deadline() {
+        let deadline = |value: &str| {
+            let mut metadata = MetadataMap::new();
+            metadata.insert(GRPC_TIMEOUT_KEY, value.parse().unwrap());
+            request_deadline(&metadata).map(|deadline| deadline.saturating_duration_since(Instant::now()))
+        };
+
+        assert!(deadline("2S").is_some_and(|left| left > Duration::from_secs(1) && left <= Duration::from_secs(2)));
+        assert!(deadline("1H").is_some_and(|left| left > Duration::from_secs(3599)));
+        assert!(deadline("500m").is_some_and(|left| left <= Duration::from_millis(500)));
+        for malformed in ["S", "5", "5x", "123456789n", "-5S"] {
+            assert_eq!(deadline(malformed), None, "{}", malformed);
+        }
+        assert_eq!(request_deadline(&MetadataMap::new()), None);
+    }
+
+    #[tokio::test]
+    async fn test_passed_deadline_is_deadline_exceeded() {
+        let auth_impl = AuthImpl::default();
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+        let s = zkp.solve(&k, &BigUint::from_bytes_be(&response.c), &x).unwrap();
+
+        let mut request = Request::new(AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() });
+        request.set_timeout(Duration::from_nanos(1));
+        let status = auth_impl.verify_authentication(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+        assert_eq!(error_code(&status), Some("timeout"));
+        assert_eq!(auth_impl.metrics().verifications_succeeded, 0);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_challenges() {
+        let key = b"challenge key".to_vec();
+        let auth_impl = AuthImpl::default().with_keyed_challenges(key.clone());
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+
+        // only the issue time is stored, c is recomputed from it
+        let challenge = auth_impl.challenges.lock().unwrap()[&response.auth_id].clone();
+        assert_eq!(challenge.c, None);
+        let c = BigUint::from_bytes_be(&response.c);
+        let salt = auth_impl.users.read().unwrap().get("alice").unwrap().salt;
+        assert_eq!(zkp.keyed_challenge(&key, &response.auth_id, &salt, challenge.issued_at_ms), c);
+
+        // another server holding the key takes over the exported challenge
+        let state = auth_impl.export_state().unwrap();
+        assert_eq!((state.challenges[0].c.as_ref(), state.challenges[0].issued_at_ms), (None, challenge.issued_at_ms));
+        let json = serde_json::to_string(&state).unwrap();
+        assert!(!json.contains("\"c\""));
+        let mut auth_impl = AuthImpl::default().with_keyed_challenges(key.clone());
+        auth_impl.import_state(serde_json::from_str(&json).unwrap()).unwrap();
+
+        let s = zkp.solve(&k, &c, &x).unwrap();
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+        auth_impl.verify_authentication(Request::new(request)).await.unwrap();
+    }
+
+    #[test]
+    fn test_salted_challenge() {
+        let zkp = ZKP::default_1024();
+        let (y1, y2) = zkp.register_commitment(&BigUint::from(6u32));
+        let (r1, r2) = zkp.register_commitment(&BigUint::from(7u32));
+        let alice = UserRegistration{y1, y2, salt: b"alice salt".to_vec()};
+        let bob = UserRegistration{salt: b"bob salt".to_vec(), ..alice.clone()};
+
+        // same y1, y2, r1, r2 and nonce, only the salts tell the challenges apart
+        let nonce = [7u8; 32];
+        let c = salted_challenge(&zkp, &alice, &nonce, &r1, &r2);
+        assert_eq!(salted_challenge(&zkp, &alice, &nonce, &r1, &r2), c);
+        assert_ne!(salted_challenge(&zkp, &bob, &nonce, &r1, &r2), c);
+    }
+
+    #[tokio::test]
+    async fn test_keyed_challenge_outlives_the_migration_window() {
+        let key = b"challenge key".to_vec();
+        let window = Duration::from_millis(200);
+        let auth_impl = AuthImpl::default().with_keyed_challenges(key.clone()).with_legacy_zkp(ZKP::toy(), window);
+        let zkp = ZKP::default_1024();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        // issued inside the window, so c is reduced mod the toy q = 11
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+        let c = BigUint::from_bytes_be(&response.c);
+        assert!(c < BigUint::from(11u32));
+
+        // answered once only the primary group is accepted
+        tokio::time::sleep(window).await;
+        assert!(auth_impl.legacy_zkp().is_none());
+        let s = zkp.solve(&k, &c, &x).unwrap();
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+        auth_impl.verify_authentication(Request::new(request)).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_revoked_session() {
+        let auth_impl = AuthImpl::default().with_zkp(ZKP::toy()).with_session_store(Box::new(InMemorySessionStore::default()));
+        let request = RegisterRequest { user: "alice".to_string(), y1: vec![2], y2: vec![3] };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        // x = 6 and k = 7 as in the toy example
+        let login = || async {
+            let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: vec![8], r2: vec![4] };
+            let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+            let s = ZKP::toy().solve(&BigUint::from(7u32), &BigUint::from_bytes_be(&response.c), &BigUint::from(6u32)).unwrap();
+            let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+            auth_impl.verify_authentication(Request::new(request)).await.unwrap().into_inner().session_id
+        };
+        let revoked = login().await;
+        let fresh = login().await;
+
+        assert_eq!(auth_impl.revoke_session(&revoked), Ok(true));
+        assert_eq!(auth_impl.revoke_session(&revoked), Ok(false));
+        assert_eq!(auth_impl.validate_session(&revoked), Err(ZKPError::InvalidSession("unknown or revoked session".to_string())));
+        assert_eq!(auth_impl.validate_session(&fresh), Ok("alice".to_string()));
+
+        // a validly signed token whose session was never stored
+        let unstored = auth_impl.sessions.issue("alice");
+        assert!(auth_impl.validate_session(&unstored).is_err());
+        assert!(AuthImpl::default().revoke_session(&fresh).is_err());
+    }
+
+    #[tokio::test]
+    async fn test_metrics_count_one_flow() {
+        let auth_impl = AuthImpl::default();
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+
+        let s = zkp.solve(&k, &BigUint::from_bytes_be(&response.c), &x).unwrap();
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+        auth_impl.verify_authentication(Request::new(request)).await.unwrap();
+
+        assert_eq!(auth_impl.metrics(), MetricsSnapshot {
+            registrations: 1,
+            challenges: 1,
+            verifications_succeeded: 1,
+            verifications_failed: 0,
+            sessions_issued: 1,
+        });
+    }
+
+    #[tokio::test]
+    async fn test_dry_run_leaves_the_challenge_open() {
+        let auth_impl = AuthImpl::default();
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+        let s = zkp.solve(&k, &BigUint::from_bytes_be(&response.c), &x).unwrap();
+
+        let wrong_s = (&s + 1u32) % zkp.q();
+        assert_eq!(auth_impl.dry_run_verify(&response.auth_id, &wrong_s).unwrap(), VerifyOutcome::BothFailed);
+        assert_eq!(auth_impl.dry_run_verify(&response.auth_id, &s).unwrap(), VerifyOutcome::Ok);
+        assert_eq!(auth_impl.dry_run_verify("unknown", &s), Err(ZKPError::ChallengeNotFound("unknown".to_string())));
+        assert_eq!(auth_impl.metrics().verifications_failed, 0);
+
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id.clone(), s: s.to_bytes_be() };
+        auth_impl.verify_authentication(Request::new(request)).await.unwrap();
+        assert!(matches!(auth_impl.dry_run_verify(&response.auth_id, &s), Err(ZKPError::ChallengeNotFound(_))));
+    }
+
+    #[tokio::test]
+    async fn test_oversized_fields_are_rejected() {
+        let auth_impl = AuthImpl::default();
+        let zkp = auth_impl.zkp();
+        let x = ZKP::generate_rand_below(zkp.q());
+        let (y1, y2) = zkp.register_commitment(&x);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: vec![0; 10_000], y2: y2.to_bytes_be() };
+        let status = auth_impl.register(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let request = RegisterRequest { user: "alice".to_string(), y1: y1.to_bytes_be(), y2: y2.to_bytes_be() };
+        auth_impl.register(Request::new(request)).await.unwrap();
+
+        let k = ZKP::generate_rand_below(zkp.q());
+        let (r1, r2) = zkp.register_commitment(&k);
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: vec![0; 10_000], r2: r2.to_bytes_be() };
+        let status = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+        let request = AuthenticationChallengeRequest { user: "alice".to_string(), r1: r1.to_bytes_be(), r2: r2.to_bytes_be() };
+        let response = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner();
+
+        // rejected before verify runs, so no verification is counted and the challenge stays open
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id.clone(), s: vec![0xff; 10_000] };
+        let status = auth_impl.verify_authentication(Request::new(request)).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::InvalidArgument);
+        assert_eq!(auth_impl.metrics().verifications_failed, 0);
+
+        let s = zkp.solve(&k, &BigUint::from_bytes_be(&response.c), &x).unwrap();
+        let request = AuthenticationAnswerRequest { auth_id: response.auth_id, s: s.to_bytes_be() };
+        assert!(auth_impl.verify_authentication(Request::new(request)).await.is_ok());
+    }
+
+    /// `InMemoryUserStore` taking `delay` for every lookup
+    struct SlowUserStore {
+        users: InMemoryUserStore,
+        delay: Duration,
+    }
+
+    impl UserStore for SlowUserStore {
+        fn get(&self, user: &str) -> Option<UserRegistration> {
+            std::thread::sleep(self.delay);
+            self.users.get(user)
+        }
+
+        fn insert(&mut self, user: String, registration: UserRegistration) -> Result<(), ZKPError> {
+            self.users.insert(user, registration)
+        }
+
+        fn remove(&mut self, user: &str) -> Result<Option<UserRegistration>, ZKPError> {
+            self.users.remove(user)
+        }
+
+        fn users(&self) -> Vec<(String, UserRegistration)> {
+            self.users.users()
+        }
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+    async fn test_user_lookups_do_not_serialize() {
+        let delay = Duration::from_millis(200);
+        let mut users = InMemoryUserStore::default();
+        for user in ["alice", "bob"] {
+            users.insert(user.to_string(), UserRegistration{y1: BigUint::from(2u32), y2: BigUint::from(3u32), salt: Vec::new()}).unwrap();
+        }
+        let auth_impl = Arc::new(AuthImpl::with_user_store(Box::new(SlowUserStore{users, delay})));
+
+        let start = Instant::now();
+        let tasks = ["alice", "bob"].map(|user| {
+            let auth_impl = auth_impl.clone();
+            tokio::spawn(async move {
+                let request = AuthenticationChallengeRequest { user: user.to_string(), r1: vec![5], r2: vec![6] };
+                auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap();
+            })
+        });
+        for task in tasks {
+            task.await.unwrap();
+        }
+
+        // one after the other would take 2 * delay
+        assert!(start.elapsed() < delay * 7 / 4, "lookups took {:?}", start.elapsed());
+    }
+
+    #[tokio::test]
+    async fn test_export_import_state() {
+        // 2, 3 and 4 are all in the order 11 subgroup mod 23
+        let auth_impl = AuthImpl::default().with_zkp(ZKP::toy());
+        for (user, y) in [("alice", 2u32), ("bob", 3u32)] {
+            let request = RegisterRequest {
+                user: user.to_string(),
+                y1: vec![y as u8],
+                y2: vec![y as u8 + 1],
+            };
+            auth_impl.register(Request::new(request)).await.unwrap();
+        }
+        let request = AuthenticationChallengeRequest {
+            user: "alice".to_string(),
+            r1: vec![5],
+            r2: vec![6],
+        };
+        let auth_id = auth_impl.create_authentication_challenge(Request::new(request)).await.unwrap().into_inner().auth_id;
+
+        let state = auth_impl.export_state().unwrap();
+        assert_eq!(state.users.len(), 2);
+        assert_eq!((&state.users["bob"].y1, &state.users["bob"].y2), (&BigUint::from(3u32), &BigUint::from(4u32)));
+        assert_eq!(state.users["bob"].salt.len(), USER_SALT_LEN);
+        assert_ne!(state.users["alice"].salt, state.users["bob"].salt);
+        assert_eq!(state.challenges.len(), 1);
+        assert_eq!(state.challenges[0].auth_id, auth_id);
+
+        let json = serde_json::to_string(&state).unwrap();
+        let state: ServerState = serde_json::from_str(&json).unwrap();
+
+        let mut migrated = AuthImpl::default().with_zkp(ZKP::toy());
+        migrated.import_state(state.clone()).unwrap();
+        assert_eq!(migrated.export_state().unwrap().users, state.users);
+        assert_eq!(migrated.challenges.lock().unwrap()[&auth_id].user, "alice");
+    }
+}
@@ -0,0 +1,307 @@
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use hmac::{Hmac, Mac};
+use num_bigint::BigUint;
+use rand::RngCore;
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use zeroize::Zeroizing;
+
+use crate::store::UserRegistration;
+use crate::ZKPError;
+
+/// How long a session token is valid unless set with `AuthImpl::with_session_ttl`
+pub const DEFAULT_SESSION_TTL: Duration = Duration::from_secs(3600);
+
+/// One login, independent of how it reaches the client, so HTTP or CLI
+/// front-ends can use it as well as the gRPC service. `SessionSigner::sign`
+/// turns it into a token and a `SessionStore` keeps it server side
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Session {
+    /// 16 random bytes in hex, the nonce of the token
+    pub id: String,
+    pub user: String,
+    pub issued_at: SystemTime,
+    pub expires_at: SystemTime,
+}
+
+impl Session {
+    /// Session for `user` issued now that expires `ttl` later
+    pub fn new(user: impl Into<String>, ttl: Duration) -> Self {
+        let mut id = [0u8; 16];
+        rand::thread_rng().fill_bytes(&mut id);
+
+        let issued_at = SystemTime::now();
+        Session {
+            id: hex::encode(id),
+            user: user.into(),
+            issued_at,
+            expires_at: issued_at + ttl,
+        }
+    }
+
+    /// cond: now >= expires_at, the session is over from the instant it expires
+    pub fn is_expired(&self, now: SystemTime) -> bool {
+        now >= self.expires_at
+    }
+}
+
+/// Issues and checks session tokens of the form
+/// payload "." hex(HMAC-SHA256(key, payload))
+/// payload = hex(user) "." expiry "." nonce
+/// with the expiry in seconds since the Unix epoch and a random 16 byte nonce
+/// in hex, so two sessions of one user never share a token
+///
+/// Downstream services holding the same key can validate tokens without
+/// talking to the Auth server
+pub struct SessionSigner {
+    key: Zeroizing<Vec<u8>>,
+    ttl: Duration,
+}
+
+impl SessionSigner {
+    pub fn new(key: Vec<u8>, ttl: Duration) -> Self {
+        SessionSigner {
+            key: Zeroizing::new(key),
+            ttl,
+        }
+    }
+
+    /// Signer with a random 32 byte key, tokens only validate in this process
+    pub fn random(ttl: Duration) -> Self {
+        let mut key = vec![0u8; 32];
+        rand::thread_rng().fill_bytes(&mut key);
+        SessionSigner::new(key, ttl)
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub fn ttl(&self) -> Duration {
+        self.ttl
+    }
+
+    /// Token for a new `Session` of `user` that expires `ttl` from now
+    pub fn issue(&self, user: &str) -> String {
+        self.sign(&Session::new(user, self.ttl))
+    }
+
+    /// Token for `session`, its expiry is rounded down to whole seconds
+    pub fn sign(&self, session: &Session) -> String {
+        let expires_at = session.expires_at.duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+        let payload = format!("{}.{}.{}", hex::encode(&session.user), expires_at, session.id);
+        let mac = hex::encode(self.mac(&payload).finalize().into_bytes());
+        format!("{}.{}", payload, mac)
+    }
+
+    /// User name in `token`, `InvalidSession` if the token is malformed, was
+    /// not signed with this key or has expired
+    pub fn validate(&self, token: &str) -> Result<String, ZKPError> {
+        self.validate_with_id(token).map(|(user, _)| user)
+    }
+
+    /// Same checks as `validate`
+    /// output = (user, session id), the id being `Session::id`
+    pub fn validate_with_id(&self, token: &str) -> Result<(String, String), ZKPError> {
+        let invalid = |reason: &str| ZKPError::InvalidSession(reason.to_string());
+
+        let (payload, mac) = token.rsplit_once('.').ok_or_else(|| invalid("malformed token"))?;
+        let mac = hex::decode(mac).map_err(|_| invalid("malformed token"))?;
+        self.mac(payload).verify_slice(&mac).map_err(|_| invalid("bad signature"))?;
+
+        let mut fields = payload.split('.');
+        let (user, expires_at, id) = match (fields.next(), fields.next(), fields.next(), fields.next()) {
+            (Some(user), Some(expires_at), Some(id), None) => (user, expires_at, id),
+            _ => return Err(invalid("malformed token")),
+        };
+        let expires_at: u64 = expires_at.parse().map_err(|_| invalid("malformed token"))?;
+        if unix_time() >= expires_at {
+            return Err(invalid("token expired"));
+        }
+
+        let user = hex::decode(user).map_err(|_| invalid("malformed token"))?;
+        let user = String::from_utf8(user).map_err(|_| invalid("malformed token"))?;
+        Ok((user, id.to_string()))
+    }
+
+    fn mac(&self, payload: &str) -> Hmac<Sha256> {
+        hmac(&self.key, payload)
+    }
+}
+
+const REGISTRATION_DOMAIN: &str = "zkp-registration:";
+
+/// Issues and checks registration tokens of the form
+/// payload "." hex(HMAC-SHA256(key, payload))
+/// payload = hex(user) "." hex(y1) "." hex(y2) "." hex(salt)
+/// The MAC input is prefixed with `REGISTRATION_DOMAIN`, so a registration token
+/// never validates as a session token even when both signers share a key
+///
+/// Lets a verifier take y1, y2 and the user's salt from the request instead of
+/// its user store, see
+/// `AuthImpl::with_registration_tokens`. Tokens do not expire and cannot be revoked
+/// short of changing the key
+pub struct RegistrationSigner {
+    key: Zeroizing<Vec<u8>>,
+}
+
+impl RegistrationSigner {
+    pub fn new(key: Vec<u8>) -> Self {
+        RegistrationSigner{key: Zeroizing::new(key)}
+    }
+
+    /// Token binding `user` to its y1, y2 and salt
+    pub fn issue(&self, user: &str, registration: &UserRegistration) -> String {
+        let payload = format!(
+            "{}.{}.{}.{}",
+            hex::encode(user),
+            hex::encode(registration.y1.to_bytes_be()),
+            hex::encode(registration.y2.to_bytes_be()),
+            hex::encode(&registration.salt),
+        );
+        let mac = hex::encode(self.mac(&payload).finalize().into_bytes());
+        format!("{}.{}", payload, mac)
+    }
+
+    /// User name and registration in `token`, `InvalidSession` if the token is
+    /// malformed or was not signed with this key. Tokens without a salt, issued
+    /// before salts were added, count as malformed and the user has to register
+    /// again
+    pub fn validate(&self, token: &str) -> Result<(String, UserRegistration), ZKPError> {
+        let invalid = |reason: &str| ZKPError::InvalidSession(reason.to_string());
+
+        let (payload, mac) = token.rsplit_once('.').ok_or_else(|| invalid("malformed registration token"))?;
+        let mac = hex::decode(mac).map_err(|_| invalid("malformed registration token"))?;
+        self.mac(payload).verify_slice(&mac).map_err(|_| invalid("bad registration token signature"))?;
+
+        let fields = payload
+            .split('.')
+            .map(hex::decode)
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(|_| invalid("malformed registration token"))?;
+        let [user, y1, y2, salt] = <[Vec<u8>; 4]>::try_from(fields).map_err(|_| invalid("malformed registration token"))?;
+
+        let user = String::from_utf8(user).map_err(|_| invalid("malformed registration token"))?;
+        let registration = UserRegistration {
+            y1: BigUint::from_bytes_be(&y1),
+            y2: BigUint::from_bytes_be(&y2),
+            salt,
+        };
+        Ok((user, registration))
+    }
+
+    fn mac(&self, payload: &str) -> Hmac<Sha256> {
+        hmac(&self.key, &format!("{}{}", REGISTRATION_DOMAIN, payload))
+    }
+}
+
+fn hmac(key: &[u8], payload: &str) -> Hmac<Sha256> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC accepts keys of any length");
+    mac.update(payload.as_bytes());
+    mac
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_valid_token() {
+        let signer = SessionSigner::random(DEFAULT_SESSION_TTL);
+        let token = signer.issue("alice");
+        assert_eq!(signer.validate(&token), Ok("alice".to_string()));
+        assert_ne!(signer.issue("alice"), token);
+
+        // same key in another service
+        let key = b"shared key".to_vec();
+        let token = SessionSigner::new(key.clone(), DEFAULT_SESSION_TTL).issue("bob.smith");
+        assert_eq!(SessionSigner::new(key, DEFAULT_SESSION_TTL).validate(&token), Ok("bob.smith".to_string()));
+    }
+
+    #[test]
+    fn test_session_expiry() {
+        let session = Session::new("alice", Duration::from_secs(60));
+        assert_eq!(session.expires_at, session.issued_at + Duration::from_secs(60));
+        assert_eq!(session.id.len(), 32);
+        assert_ne!(Session::new("alice", Duration::from_secs(60)).id, session.id);
+
+        // expired exactly at expires_at, not a nanosecond before
+        assert!(!session.is_expired(session.issued_at));
+        assert!(!session.is_expired(session.expires_at - Duration::from_nanos(1)));
+        assert!(session.is_expired(session.expires_at));
+        assert!(session.is_expired(session.expires_at + Duration::from_nanos(1)));
+
+        let session = Session::new("alice", Duration::ZERO);
+        assert!(session.is_expired(session.issued_at));
+    }
+
+    #[test]
+    fn test_signed_session() {
+        let signer = SessionSigner::random(DEFAULT_SESSION_TTL);
+        let session = Session::new("alice", DEFAULT_SESSION_TTL);
+        let token = signer.sign(&session);
+        assert_eq!(signer.validate(&token), Ok("alice".to_string()));
+        assert_eq!(token.split('.').nth(2), Some(session.id.as_str()));
+        assert_eq!(signer.validate_with_id(&token), Ok(("alice".to_string(), session.id)));
+    }
+
+    #[test]
+    fn test_expired_token() {
+        let signer = SessionSigner::random(Duration::from_secs(0));
+        let token = signer.issue("alice");
+        assert_eq!(signer.validate(&token), Err(ZKPError::InvalidSession("token expired".to_string())));
+    }
+
+    #[test]
+    fn test_tampered_token() {
+        let signer = SessionSigner::random(DEFAULT_SESSION_TTL);
+        let token = signer.issue("alice");
+        let (_, rest) = token.split_once('.').unwrap();
+
+        // another user name with the signature of alice's token
+        let forged = format!("{}.{}", hex::encode("mallory"), rest);
+        assert_eq!(signer.validate(&forged), Err(ZKPError::InvalidSession("bad signature".to_string())));
+
+        // pushed back expiry
+        let fields: Vec<&str> = token.split('.').collect();
+        let later = fields[1].parse::<u64>().unwrap() + 3600;
+        let extended = format!("{}.{}.{}.{}", fields[0], later, fields[2], fields[3]);
+        assert_eq!(signer.validate(&extended), Err(ZKPError::InvalidSession("bad signature".to_string())));
+
+        // signed with another key
+        assert!(SessionSigner::random(DEFAULT_SESSION_TTL).validate(&token).is_err());
+        assert!(signer.validate("not a token").is_err());
+    }
+
+    #[test]
+    fn test_registration_token() {
+        let signer = RegistrationSigner::new(b"registration key".to_vec());
+        let registration = UserRegistration{y1: BigUint::from(2u32), y2: BigUint::from(3u32), salt: b"salt".to_vec()};
+
+        let token = signer.issue("alice", &registration);
+        assert_eq!(signer.validate(&token), Ok(("alice".to_string(), registration.clone())));
+
+        // y1 swapped for one the holder knows the discrete log of
+        let fields: Vec<&str> = token.split('.').collect();
+        let forged = format!("{}.{}.{}.{}.{}", fields[0], hex::encode([4u8]), fields[2], fields[3], fields[4]);
+        assert_eq!(signer.validate(&forged), Err(ZKPError::InvalidSession("bad registration token signature".to_string())));
+
+        // another user's salt
+        let forged = format!("{}.{}.{}.{}.{}", fields[0], fields[1], fields[2], hex::encode(b"other"), fields[4]);
+        assert_eq!(signer.validate(&forged), Err(ZKPError::InvalidSession("bad registration token signature".to_string())));
+
+        // a validly signed token without a salt
+        let payload = [fields[0], fields[1], fields[2]].join(".");
+        let unsalted = format!("{}.{}", payload, hex::encode(signer.mac(&payload).finalize().into_bytes()));
+        assert_eq!(signer.validate(&unsalted), Err(ZKPError::InvalidSession("malformed registration token".to_string())));
+
+        assert!(RegistrationSigner::new(b"other key".to_vec()).validate(&token).is_err());
+        assert!(signer.validate("alice").is_err());
+    }
+}
@@ -0,0 +1,326 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::SystemTime;
+
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::session::Session;
+use crate::ZKPError;
+
+/// Public commitments sent by the prover at registration
+/// y1 = alpha^x mod p
+/// y2 = beta^x mod p
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UserRegistration {
+    #[serde(with = "crate::proof::biguint_hex")]
+    pub y1: BigUint,
+    #[serde(with = "crate::proof::biguint_hex")]
+    pub y2: BigUint,
+    /// Random bytes drawn by the server at registration and mixed into every
+    /// challenge for this user, so two users with the same y1, y2 never get the
+    /// same challenge. Empty for registrations stored before salts were added
+    #[serde(default, with = "crate::proof::bytes_hex")]
+    pub salt: Vec<u8>,
+}
+
+/// Storage for registered users, keyed by user name
+///
+/// `Sync` so `AuthImpl` can serve lookups from several requests at once
+pub trait UserStore: Send + Sync {
+    fn get(&self, user: &str) -> Option<UserRegistration>;
+
+    /// Replaces any existing registration for `user`
+    fn insert(&mut self, user: String, registration: UserRegistration) -> Result<(), ZKPError>;
+
+    fn remove(&mut self, user: &str) -> Result<Option<UserRegistration>, ZKPError>;
+
+    /// Every registration, in no particular order
+    fn users(&self) -> Vec<(String, UserRegistration)>;
+
+    /// Writes pending state to durable storage, a no-op for in-memory stores
+    fn flush(&mut self) -> Result<(), ZKPError> {
+        Ok(())
+    }
+}
+
+/// Registrations are lost when the process exits
+#[derive(Debug, Default)]
+pub struct InMemoryUserStore {
+    users: HashMap<String, UserRegistration>,
+}
+
+impl UserStore for InMemoryUserStore {
+    fn get(&self, user: &str) -> Option<UserRegistration> {
+        self.users.get(user).cloned()
+    }
+
+    fn insert(&mut self, user: String, registration: UserRegistration) -> Result<(), ZKPError> {
+        self.users.insert(user, registration);
+        Ok(())
+    }
+
+    fn remove(&mut self, user: &str) -> Result<Option<UserRegistration>, ZKPError> {
+        Ok(self.users.remove(user))
+    }
+
+    fn users(&self) -> Vec<(String, UserRegistration)> {
+        self.users.iter().map(|(user, registration)| (user.clone(), registration.clone())).collect()
+    }
+}
+
+/// Keeps every registration in memory and rewrites the whole JSON file on
+/// each `insert`/`remove`
+#[derive(Debug)]
+pub struct JsonFileUserStore {
+    path: PathBuf,
+    users: HashMap<String, UserRegistration>,
+}
+
+impl JsonFileUserStore {
+    /// Loads the users from `path`, a missing file starts an empty store
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ZKPError> {
+        let path = path.as_ref().to_path_buf();
+
+        let users = if path.exists() {
+            let contents = fs::read_to_string(&path).map_err(|e| ZKPError::Storage(e.to_string()))?;
+            serde_json::from_str(&contents).map_err(|e| ZKPError::Storage(e.to_string()))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(JsonFileUserStore { path, users })
+    }
+
+}
+
+impl UserStore for JsonFileUserStore {
+    fn get(&self, user: &str) -> Option<UserRegistration> {
+        self.users.get(user).cloned()
+    }
+
+    fn insert(&mut self, user: String, registration: UserRegistration) -> Result<(), ZKPError> {
+        self.users.insert(user, registration);
+        self.flush()
+    }
+
+    fn remove(&mut self, user: &str) -> Result<Option<UserRegistration>, ZKPError> {
+        let removed = self.users.remove(user);
+        self.flush()?;
+        Ok(removed)
+    }
+
+    fn users(&self) -> Vec<(String, UserRegistration)> {
+        self.users.iter().map(|(user, registration)| (user.clone(), registration.clone())).collect()
+    }
+
+    fn flush(&mut self) -> Result<(), ZKPError> {
+        let contents = serde_json::to_string(&self.users).map_err(|e| ZKPError::Storage(e.to_string()))?;
+        fs::write(&self.path, contents).map_err(|e| ZKPError::Storage(e.to_string()))
+    }
+}
+
+/// Server-side record of the sessions issued on successful logins, keyed by
+/// `Session::id`, so a session can be revoked before its token expires
+///
+/// Kept apart from the challenges: a session outlives the challenge it was
+/// issued for by far. `Sync` like `UserStore`
+pub trait SessionStore: Send + Sync {
+    fn get(&self, session_id: &str) -> Option<Session>;
+
+    /// Replaces any existing session with the same id
+    fn insert(&mut self, session: Session) -> Result<(), ZKPError>;
+
+    /// Removes the session, output = whether it was stored
+    fn revoke(&mut self, session_id: &str) -> Result<bool, ZKPError>;
+
+    /// The stored session under `session_id`, `InvalidSession` if it was never
+    /// stored, is revoked or has expired at `now`
+    fn validate(&self, session_id: &str, now: SystemTime) -> Result<Session, ZKPError> {
+        let session = self.get(session_id).ok_or_else(|| ZKPError::InvalidSession("unknown or revoked session".to_string()))?;
+        if session.is_expired(now) {
+            return Err(ZKPError::InvalidSession("session expired".to_string()));
+        }
+        Ok(session)
+    }
+
+    /// Writes pending state to durable storage, a no-op for in-memory stores
+    fn flush(&mut self) -> Result<(), ZKPError> {
+        Ok(())
+    }
+}
+
+/// Sessions are lost when the process exits
+#[derive(Debug, Default)]
+pub struct InMemorySessionStore {
+    sessions: HashMap<String, Session>,
+}
+
+impl SessionStore for InMemorySessionStore {
+    fn get(&self, session_id: &str) -> Option<Session> {
+        self.sessions.get(session_id).cloned()
+    }
+
+    fn insert(&mut self, session: Session) -> Result<(), ZKPError> {
+        self.sessions.insert(session.id.clone(), session);
+        Ok(())
+    }
+
+    fn revoke(&mut self, session_id: &str) -> Result<bool, ZKPError> {
+        Ok(self.sessions.remove(session_id).is_some())
+    }
+}
+
+/// Keeps every session in memory and rewrites the whole JSON file on each
+/// `insert`/`revoke`, as `JsonFileUserStore` does. Expired sessions are
+/// dropped on every write
+#[derive(Debug)]
+pub struct JsonFileSessionStore {
+    path: PathBuf,
+    sessions: HashMap<String, Session>,
+}
+
+impl JsonFileSessionStore {
+    /// Loads the sessions from `path`, a missing file starts an empty store
+    pub fn open<P: AsRef<Path>>(path: P) -> Result<Self, ZKPError> {
+        let path = path.as_ref().to_path_buf();
+
+        let sessions = if path.exists() {
+            let contents = fs::read_to_string(&path).map_err(|e| ZKPError::Storage(e.to_string()))?;
+            serde_json::from_str(&contents).map_err(|e| ZKPError::Storage(e.to_string()))?
+        } else {
+            HashMap::new()
+        };
+
+        Ok(JsonFileSessionStore { path, sessions })
+    }
+}
+
+impl SessionStore for JsonFileSessionStore {
+    fn get(&self, session_id: &str) -> Option<Session> {
+        self.sessions.get(session_id).cloned()
+    }
+
+    fn insert(&mut self, session: Session) -> Result<(), ZKPError> {
+        self.sessions.insert(session.id.clone(), session);
+        self.flush()
+    }
+
+    fn revoke(&mut self, session_id: &str) -> Result<bool, ZKPError> {
+        let revoked = self.sessions.remove(session_id).is_some();
+        self.flush()?;
+        Ok(revoked)
+    }
+
+    fn flush(&mut self) -> Result<(), ZKPError> {
+        let now = SystemTime::now();
+        self.sessions.retain(|_, session| !session.is_expired(now));
+
+        let contents = serde_json::to_string(&self.sessions).map_err(|e| ZKPError::Storage(e.to_string()))?;
+        fs::write(&self.path, contents).map_err(|e| ZKPError::Storage(e.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::time::Duration;
+
+    fn registration() -> UserRegistration {
+        UserRegistration {
+            y1: BigUint::from(2u32),
+            y2: BigUint::from(3u32),
+            salt: vec![7; 16],
+        }
+    }
+
+    #[test]
+    fn test_in_memory_store() {
+        let mut store = InMemoryUserStore::default();
+        assert_eq!(store.get("alice"), None);
+
+        store.insert("alice".to_string(), registration()).unwrap();
+        assert_eq!(store.get("alice"), Some(registration()));
+        assert_eq!(store.users(), vec![("alice".to_string(), registration())]);
+
+        assert_eq!(store.remove("alice").unwrap(), Some(registration()));
+        assert_eq!(store.get("alice"), None);
+    }
+
+    #[test]
+    fn test_json_file_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+
+        let mut store = JsonFileUserStore::open(&path).unwrap();
+        store.insert("alice".to_string(), registration()).unwrap();
+        store.insert("bob".to_string(), registration()).unwrap();
+        store.remove("bob").unwrap();
+        drop(store);
+
+        let store = JsonFileUserStore::open(&path).unwrap();
+        assert_eq!(store.get("alice"), Some(registration()));
+        assert_eq!(store.get("bob"), None);
+    }
+
+    #[test]
+    fn test_json_file_store_without_salts() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        fs::write(&path, r#"{"alice":{"y1":"02","y2":"03"}}"#).unwrap();
+
+        let store = JsonFileUserStore::open(&path).unwrap();
+        assert_eq!(store.get("alice"), Some(UserRegistration{salt: Vec::new(), ..registration()}));
+    }
+
+    #[test]
+    fn test_revoked_session_fails_validation() {
+        let dir = tempfile::tempdir().unwrap();
+        let stores: [Box<dyn SessionStore>; 2] = [
+            Box::new(InMemorySessionStore::default()),
+            Box::new(JsonFileSessionStore::open(dir.path().join("sessions.json")).unwrap()),
+        ];
+
+        for mut store in stores {
+            let fresh = Session::new("alice", Duration::from_secs(60));
+            let revoked = Session::new("alice", Duration::from_secs(60));
+            store.insert(fresh.clone()).unwrap();
+            store.insert(revoked.clone()).unwrap();
+
+            assert!(store.revoke(&revoked.id).unwrap());
+            assert!(!store.revoke(&revoked.id).unwrap());
+
+            let now = SystemTime::now();
+            assert_eq!(store.validate(&fresh.id, now), Ok(fresh.clone()));
+            assert_eq!(store.validate(&revoked.id, now), Err(ZKPError::InvalidSession("unknown or revoked session".to_string())));
+            assert_eq!(store.validate(&fresh.id, fresh.expires_at), Err(ZKPError::InvalidSession("session expired".to_string())));
+        }
+    }
+
+    #[test]
+    fn test_json_file_session_store_survives_restart() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sessions.json");
+
+        let session = Session::new("alice", Duration::from_secs(60));
+        let expired = Session::new("bob", Duration::from_secs(0));
+        let mut store = JsonFileSessionStore::open(&path).unwrap();
+        store.insert(expired.clone()).unwrap();
+        store.insert(session.clone()).unwrap();
+        drop(store);
+
+        let store = JsonFileSessionStore::open(&path).unwrap();
+        assert_eq!(store.get(&session.id), Some(session));
+        assert_eq!(store.get(&expired.id), None);
+    }
+
+    #[test]
+    fn test_json_file_store_rejects_corrupt_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("users.json");
+        fs::write(&path, "not json").unwrap();
+
+        assert!(matches!(JsonFileUserStore::open(&path), Err(ZKPError::Storage(_))));
+    }
+}
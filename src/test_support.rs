@@ -0,0 +1,66 @@
+//! Hex helpers for the unit tests inside the library. Only compiled with
+//! `cfg(test)`, so the integration tests under tests/ and the tests of the
+//! binaries cannot use them
+
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+
+/// Big-endian bytes of `hex`, whitespace anywhere is ignored so constants can
+/// be pasted as RFCs print them. Leading zero bytes are kept, and an odd number
+/// of digits is read as if the first one had a 0 in front: "abc" = [0x0a, 0xbc]
+///
+/// Panics on any other character, the input is always a test constant
+pub fn bytes_from_hex(hex: &str) -> Vec<u8> {
+    let mut digits: Vec<u8> = hex
+        .chars()
+        .filter(|c| !c.is_ascii_whitespace())
+        .map(|c| c.to_digit(16).unwrap_or_else(|| panic!("{:?} is not a hex digit in {:?}", c, hex)) as u8)
+        .collect();
+    if digits.len() % 2 == 1 {
+        digits.insert(0, 0);
+    }
+
+    digits.chunks(2).map(|pair| pair[0] << 4 | pair[1]).collect()
+}
+
+/// `bytes_from_hex` read as a big-endian number
+pub fn bn_from_hex(hex: &str) -> BigUint {
+    BigUint::from_bytes_be(&bytes_from_hex(hex))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use alloc::vec;
+
+    #[test]
+    fn test_bn_from_hex() {
+        assert_eq!(bn_from_hex("F518AA87"), BigUint::from(0xF518AA87u32));
+        assert_eq!(bn_from_hex("f518aa87"), BigUint::from(0xF518AA87u32));
+        assert_eq!(bn_from_hex(" F518\n  AA87 "), BigUint::from(0xF518AA87u32));
+        assert_eq!(bn_from_hex(""), BigUint::from(0u32));
+    }
+
+    #[test]
+    fn test_odd_length_hex() {
+        assert_eq!(bytes_from_hex("abc"), vec![0x0a, 0xbc]);
+        assert_eq!(bn_from_hex("abc"), BigUint::from(0xabcu32));
+        assert_eq!(bytes_from_hex("f"), vec![0x0f]);
+        // the same number as the even-length spelling
+        assert_eq!(bn_from_hex("123"), bn_from_hex("0123"));
+    }
+
+    #[test]
+    fn test_leading_zeros_are_kept() {
+        assert_eq!(bytes_from_hex("000f"), vec![0x00, 0x0f]);
+        assert_eq!(bytes_from_hex("00 00 01"), vec![0, 0, 1]);
+        assert_eq!(bn_from_hex("000f"), BigUint::from(15u32));
+    }
+
+    #[test]
+    #[should_panic(expected = "is not a hex digit")]
+    fn test_invalid_hex() {
+        bn_from_hex("0x1f");
+    }
+}
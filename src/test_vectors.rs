@@ -0,0 +1,83 @@
+//! Known-answer vectors for other implementations of the protocol, the JSON
+//! lives in vectors/ at the root of the repository. Values are big-endian hex
+use num_bigint::BigUint;
+use serde::{Deserialize, Serialize};
+
+use crate::proof::biguint_hex;
+use crate::{ZKPError, ZKP};
+
+/// vectors/rfc5114-1024.json
+pub const RFC5114_1024_JSON: &str = include_str!("../vectors/rfc5114-1024.json");
+
+/// Group parameters and the vectors computed in that group
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVectors {
+    /// Name accepted by `ZKP::from_named_group`
+    pub group: String,
+    #[serde(with = "biguint_hex")]
+    pub p: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub q: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub alpha: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub beta: BigUint,
+    pub vectors: Vec<TestVector>,
+}
+
+/// Inputs x, k, c and the expected outputs
+/// y1 = alpha^x mod p, y2 = beta^x mod p
+/// r1 = alpha^k mod p, r2 = beta^k mod p
+/// s = k - c*x mod q
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TestVector {
+    #[serde(with = "biguint_hex")]
+    pub x: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub k: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub c: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub y1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub y2: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r1: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub r2: BigUint,
+    #[serde(with = "biguint_hex")]
+    pub s: BigUint,
+}
+
+/// Parses `RFC5114_1024_JSON`
+pub fn rfc5114_1024() -> Result<TestVectors, ZKPError> {
+    serde_json::from_str(RFC5114_1024_JSON).map_err(|e| ZKPError::Decode(e.to_string()))
+}
+
+impl TestVectors {
+    /// The group the vectors were computed in, with the same checks as `ZKP::new`
+    pub fn zkp(&self) -> Result<ZKP, ZKPError> {
+        ZKP::new(self.p.clone(), self.q.clone(), self.alpha.clone(), self.beta.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_rfc5114_1024_vectors() {
+        let vectors = rfc5114_1024().unwrap();
+        let zkp = vectors.zkp().unwrap();
+
+        let named = ZKP::from_named_group(&vectors.group).unwrap();
+        assert_eq!((zkp.p(), zkp.q(), zkp.alpha(), zkp.beta()), (named.p(), named.q(), named.alpha(), named.beta()));
+
+        assert!(!vectors.vectors.is_empty());
+        for vector in &vectors.vectors {
+            assert_eq!(zkp.register_commitment(&vector.x), (vector.y1.clone(), vector.y2.clone()));
+            assert_eq!(zkp.prove(&vector.x, &vector.k, &vector.c).unwrap(), (vector.r1.clone(), vector.r2.clone(), vector.s.clone()));
+            assert!(zkp.verify(&vector.r1, &vector.r2, &vector.y1, &vector.y2, &vector.c, &vector.s));
+        }
+    }
+}
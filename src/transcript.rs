@@ -0,0 +1,105 @@
+//! Labeled Fiat-Shamir transcript for protocols built on top of `ZKP`
+//!
+//! Every message is absorbed as len(label) || label || len(value) || value with
+//! 8 byte big-endian lengths, so two different sequences of appends never hash
+//! the same bytes, unlike a flat concatenation of values. Each challenge is
+//! absorbed back into the transcript, so later challenges depend on it
+
+use num_bigint::BigUint;
+use sha2::{Digest, Sha512};
+
+/// Running SHA-512 over the labeled messages of one protocol run
+///
+/// Not to be confused with `proof::Transcript`, the record of one finished proof
+#[derive(Clone)]
+pub struct FiatShamirTranscript {
+    hasher: Sha512,
+    q: BigUint,
+}
+
+impl FiatShamirTranscript {
+    /// Transcript for challenges in [1, q), with `domain` naming the protocol
+    /// so transcripts of different protocols never collide
+    pub fn new(domain: &str, q: &BigUint) -> Self {
+        let mut transcript = FiatShamirTranscript{hasher: Sha512::new(), q: q.clone()};
+        transcript.absorb(b"domain", domain.as_bytes());
+        transcript
+    }
+
+    /// Absorbs `value` big-endian under `label`
+    pub fn append(&mut self, label: &str, value: &BigUint) {
+        self.absorb(label.as_bytes(), &value.to_bytes_be());
+    }
+
+    /// output = c in [1, q) derived from every message so far and `label`
+    ///
+    /// 128 bits more than q has are expanded from the state in counter mode and
+    /// reduced, so c is close to uniform for any size of q
+    pub fn challenge(&mut self, label: &str) -> BigUint {
+        self.absorb(b"challenge", label.as_bytes());
+
+        let blocks = (self.q.bits() as usize + 128).div_ceil(512);
+        let mut bytes = Vec::with_capacity(64 * blocks);
+        for counter in 0..blocks as u64 {
+            let mut hasher = self.hasher.clone();
+            hasher.update(counter.to_be_bytes());
+            bytes.extend(hasher.finalize());
+        }
+
+        let one = BigUint::from(1u32);
+        let c = BigUint::from_bytes_be(&bytes) % (&self.q - &one) + one;
+        self.append(label, &c);
+        c
+    }
+
+    fn absorb(&mut self, label: &[u8], value: &[u8]) {
+        for bytes in [label, value] {
+            self.hasher.update((bytes.len() as u64).to_be_bytes());
+            self.hasher.update(bytes);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::ZKP;
+
+    fn transcript(appends: &[(&str, u32)]) -> FiatShamirTranscript {
+        let mut transcript = FiatShamirTranscript::new("test", ZKP::default_1024().q());
+        for (label, value) in appends {
+            transcript.append(label, &BigUint::from(*value));
+        }
+        transcript
+    }
+
+    #[test]
+    fn test_challenge_depends_on_order_and_labels() {
+        let c = transcript(&[("r1", 1), ("r2", 2)]).challenge("c");
+        assert_eq!(transcript(&[("r1", 1), ("r2", 2)]).challenge("c"), c);
+
+        assert_ne!(transcript(&[("r2", 2), ("r1", 1)]).challenge("c"), c);
+        assert_ne!(transcript(&[("r1", 2), ("r2", 1)]).challenge("c"), c);
+        assert_ne!(transcript(&[("r1", 1), ("r2", 2)]).challenge("c2"), c);
+        assert_ne!(FiatShamirTranscript::new("other", ZKP::default_1024().q()).challenge("c"), transcript(&[]).challenge("c"));
+
+        // the same bytes split differently between label and value
+        assert_ne!(transcript(&[("a", 0x62)]).challenge("c"), transcript(&[("ab", 0)]).challenge("c"));
+    }
+
+    #[test]
+    fn test_challenges_chain() {
+        let mut transcript = transcript(&[("r1", 1)]);
+        let c1 = transcript.challenge("c");
+        let c2 = transcript.challenge("c");
+        assert_ne!(c1, c2);
+
+        for zkp in [ZKP::toy(), ZKP::default_1024(), ZKP::default_2048()] {
+            let mut transcript = FiatShamirTranscript::new("test", zkp.q());
+            for _ in 0..20 {
+                let c = transcript.challenge("c");
+                assert!(c > BigUint::from(0u32) && &c < zkp.q());
+            }
+        }
+    }
+}
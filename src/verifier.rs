@@ -0,0 +1,88 @@
+use num_bigint::BigUint;
+
+use crate::{Commitment, VerifierRole, ZKP};
+
+/// Verifier side of the protocol with an injectable challenge source, e.g. a
+/// constant for tests or another transport's challenge generator
+///
+/// `Verifier::random` draws c like the gRPC service does
+pub struct Verifier<F> {
+    zkp: ZKP,
+    challenge: F,
+}
+
+impl<F: FnMut(&Commitment) -> BigUint> Verifier<F> {
+    /// `challenge` gets the registered y1, y2 of the prover and returns c in [1, q)
+    pub fn new(zkp: ZKP, challenge: F) -> Self {
+        Verifier{zkp, challenge}
+    }
+}
+
+/// `challenge` asks the injected source, `verify` is the default `ZKP::verify`
+impl<F: FnMut(&Commitment) -> BigUint> VerifierRole for Verifier<F> {
+    fn zkp(&self) -> &ZKP {
+        &self.zkp
+    }
+
+    fn challenge(&mut self, commitment: &Commitment) -> BigUint {
+        (self.challenge)(commitment)
+    }
+}
+
+impl Verifier<fn(&Commitment) -> BigUint> {
+    /// Verifier drawing every challenge uniformly from [1, q)
+    pub fn random(zkp: ZKP) -> Verifier<impl FnMut(&Commitment) -> BigUint> {
+        let q = zkp.q().clone();
+        let zero = BigUint::from(0u32);
+
+        Verifier::new(zkp, move |_: &Commitment| loop {
+            let c = ZKP::generate_rand_below(&q);
+            if c != zero {
+                return c;
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Proof;
+
+    #[test]
+    fn test_constant_challenge() {
+        let zkp = ZKP::toy();
+        let mut verifier = Verifier::new(zkp, |_: &Commitment| BigUint::from(4u32));
+
+        let x = BigUint::from(6u32);
+        let k = BigUint::from(7u32);
+        let (y1, y2) = verifier.zkp().register_commitment(&x);
+        let commitment = Commitment{y1, y2};
+        assert_eq!(commitment, Commitment{y1: BigUint::from(2u32), y2: BigUint::from(3u32)});
+
+        let c = verifier.challenge(&commitment);
+        let (r1, r2, s) = verifier.zkp().prove(&x, &k, &c).unwrap();
+        assert_eq!((&r1, &r2, &s), (&BigUint::from(8u32), &BigUint::from(4u32), &BigUint::from(5u32)));
+        assert!(verifier.verify(&commitment, &Proof{r1: r1.clone(), r2: r2.clone(), c: c.clone(), s}));
+
+        let s_fake = verifier.zkp().solve(&k, &c, &BigUint::from(7u32)).unwrap();
+        assert!(!verifier.verify(&commitment, &Proof{r1, r2, c, s: s_fake}));
+    }
+
+    #[test]
+    fn test_random_challenge() {
+        let prover = ZKP::default_1024();
+        let mut verifier = Verifier::random(ZKP::default_1024());
+
+        let x = ZKP::generate_rand_below(prover.q());
+        let (y1, y2) = prover.register_commitment(&x);
+        let commitment = Commitment{y1, y2};
+
+        let session = prover.prove_with_auto_nonce(&x);
+        let (r1, r2) = (session.r1().clone(), session.r2().clone());
+        let c = verifier.challenge(&commitment);
+        let s = session.respond(&c).unwrap();
+
+        assert!(verifier.verify(&commitment, &Proof{r1, r2, c, s}));
+    }
+}
@@ -0,0 +1,69 @@
+//! wasm-bindgen wrapper over `core` for provers running in the browser, so x
+//! never leaves the device
+//!
+//! Every number is passed as big-endian bytes. Outputs holding two elements
+//! are left-padded to the byte width of p each and concatenated, as in
+//! `ZKP::encode_fixed`. One login is:
+//! k = nonce(q), (r1, r2) = commitments(p, alpha, beta, k), send r1 and r2,
+//! then s = prove(q, x, k, c) for the challenge c
+
+use alloc::string::ToString;
+use alloc::vec;
+use alloc::vec::Vec;
+
+use num_bigint::BigUint;
+use wasm_bindgen::prelude::*;
+
+use crate::core;
+
+/// output = y1 || y2 = alpha^x mod p || beta^x mod p, also r1 || r2 for x = k
+#[wasm_bindgen]
+pub fn commitments(p: &[u8], alpha: &[u8], beta: &[u8], x: &[u8]) -> Vec<u8> {
+    let p = BigUint::from_bytes_be(p);
+    let x = BigUint::from_bytes_be(x);
+    let width = p.bits().div_ceil(8) as usize;
+
+    let mut output = Vec::with_capacity(2 * width);
+    for generator in [alpha, beta] {
+        let element = core::exponentiate(&BigUint::from_bytes_be(generator), &x, &p);
+        output.extend(encode_fixed(&element, width));
+    }
+    output
+}
+
+/// output = s = k - c*x mod q, throws for q = 0
+#[wasm_bindgen]
+pub fn prove(q: &[u8], x: &[u8], k: &[u8], c: &[u8]) -> Result<Vec<u8>, JsError> {
+    let [q, x, k, c] = [q, x, k, c].map(BigUint::from_bytes_be);
+
+    core::solve(&k, &c, &x, &q).map(|s| s.to_bytes_be()).map_err(|e| JsError::new(&e.to_string()))
+}
+
+/// Random nonce k in [1, q) from the platform RNG, `crypto.getRandomValues`
+/// in the browser
+///
+/// 64 more random bits than q has are reduced mod q, so the bias is negligible
+#[wasm_bindgen]
+pub fn nonce(q: &[u8]) -> Result<Vec<u8>, JsError> {
+    let q = BigUint::from_bytes_be(q);
+    if q <= BigUint::from(1u32) {
+        return Err(JsError::new("q must be greater than 1"));
+    }
+
+    let zero = BigUint::from(0u32);
+    let mut bytes = vec![0u8; q.bits().div_ceil(8) as usize + 8];
+    loop {
+        getrandom::getrandom(&mut bytes).map_err(|e| JsError::new(&e.to_string()))?;
+        let k = BigUint::from_bytes_be(&bytes) % &q;
+        if k != zero {
+            return Ok(k.to_bytes_be());
+        }
+    }
+}
+
+fn encode_fixed(value: &BigUint, width: usize) -> Vec<u8> {
+    let bytes = value.to_bytes_be();
+    let mut padded = vec![0u8; width.saturating_sub(bytes.len())];
+    padded.extend(bytes);
+    padded
+}
@@ -12,8 +12,15 @@ pub struct RegisterRequest {
     #[prost(bytes = "vec", tag = "3")]
     pub y2: ::prost::alloc::vec::Vec<u8>,
 }
-#[derive(Clone, Copy, PartialEq, ::prost::Message)]
-pub struct RegisterResponse {}
+///
+/// registration_token is only set when the server runs without a user store,
+/// it must then be sent as "zkp-registration-token" metadata with every
+/// AuthenticationAnswerRequest
+#[derive(Clone, PartialEq, ::prost::Message)]
+pub struct RegisterResponse {
+    #[prost(string, tag = "1")]
+    pub registration_token: ::prost::alloc::string::String,
+}
 ///
 /// Prover asks for challenge in the server sending r1, r2
 /// r1 = alpha^k mod p
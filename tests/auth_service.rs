@@ -0,0 +1,551 @@
+#![cfg(feature = "std")]
+
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::time::Duration;
+
+use num_bigint::BigUint;
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::{Channel, Server};
+use zkp_chaum_pedersen::auth_client;
+use tonic_health::pb::{health_check_response::ServingStatus, health_client::HealthClient, HealthCheckRequest};
+use zkp_chaum_pedersen::service::{error_code, is_retryable, router, serve_with_shutdown, AuthImpl, REGISTRATION_TOKEN_KEY};
+use zkp_chaum_pedersen::session::{SessionSigner, DEFAULT_SESSION_TTL};
+use zkp_chaum_pedersen::store::JsonFileUserStore;
+use zkp_chaum_pedersen::{Secret, ZKPError, ZKP};
+use zkp_chaum_pedersen::zkp_auth::{auth_client::AuthClient, auth_server::AuthServer, AuthenticationAnswerRequest, AuthenticationChallengeRequest, RegisterRequest};
+
+/// Starts the server on a random local port and returns its address
+async fn spawn_server(auth_impl: AuthImpl) -> SocketAddr {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+
+    tokio::spawn(async move {
+        Server::builder()
+            .add_service(AuthServer::new(auth_impl))
+            .serve_with_incoming(TcpListenerStream::new(listener))
+            .await
+            .unwrap();
+    });
+
+    addr
+}
+
+async fn connect(addr: SocketAddr) -> AuthClient<Channel> {
+    AuthClient::connect(format!("http://{}", addr)).await.unwrap()
+}
+
+/// Registers `user` with secret `x` in the 1024-bit group
+async fn register(client: &mut AuthClient<Channel>, zkp: &ZKP, user: &str, x: &BigUint) {
+    let request = RegisterRequest {
+        user: user.to_string(),
+        y1: ZKP::exponentiate(zkp.alpha(), x, zkp.p()).to_bytes_be(),
+        y2: ZKP::exponentiate(zkp.beta(), x, zkp.p()).to_bytes_be(),
+    };
+    client.register(request).await.unwrap();
+}
+
+/// Sends fresh r1, r2 for `user` and returns (auth_id, k, c)
+async fn challenge(client: &mut AuthClient<Channel>, zkp: &ZKP, user: &str) -> (String, BigUint, BigUint) {
+    challenge_with_token(client, zkp, user, None).await.unwrap()
+}
+
+/// `challenge` with the registration token of `user` in stateless mode
+async fn challenge_with_token(client: &mut AuthClient<Channel>, zkp: &ZKP, user: &str, token: Option<&str>) -> Result<(String, BigUint, BigUint), tonic::Status> {
+    let k = ZKP::generate_rand_below(zkp.q());
+    let mut request = tonic::Request::new(AuthenticationChallengeRequest {
+        user: user.to_string(),
+        r1: ZKP::exponentiate(zkp.alpha(), &k, zkp.p()).to_bytes_be(),
+        r2: ZKP::exponentiate(zkp.beta(), &k, zkp.p()).to_bytes_be(),
+    });
+    if let Some(token) = token {
+        request.metadata_mut().insert(REGISTRATION_TOKEN_KEY, token.parse().unwrap());
+    }
+    let response = client.create_authentication_challenge(request).await?.into_inner();
+
+    Ok((response.auth_id, k, BigUint::from_bytes_be(&response.c)))
+}
+
+#[tokio::test]
+async fn test_register() {
+    let addr = spawn_server(AuthImpl::default()).await;
+    let mut client = connect(addr).await;
+    let zkp = ZKP::default_1024();
+    let (y1, y2) = zkp.register_commitment(&zkp.generate_challenge());
+
+    let request = RegisterRequest {
+        user: "alice".to_string(),
+        y1: y1.to_bytes_be(),
+        y2: y2.to_bytes_be(),
+    };
+    assert!(client.register(request.clone()).await.is_ok());
+
+    let status = client.register(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::AlreadyExists);
+
+    // y1 = p is not an element of the group
+    let request = RegisterRequest {
+        user: "bob".to_string(),
+        y1: zkp.p().to_bytes_be(),
+        y2: y2.to_bytes_be(),
+    };
+    let status = client.register(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+
+    // y1 = p - 1 is an element of order 2, outside the order q subgroup
+    let request = RegisterRequest {
+        user: "bob".to_string(),
+        y1: (zkp.p() - 1u32).to_bytes_be(),
+        y2: y2.to_bytes_be(),
+    };
+    let status = client.register(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::InvalidArgument);
+    assert!(status.message().contains("y1 is not in the order q subgroup"));
+}
+
+#[tokio::test]
+async fn test_create_authentication_challenge() {
+    // y1, y2, r1 and r2 of the toy example, x = 6 and k = 7
+    let auth_impl = AuthImpl::default().with_zkp(ZKP::toy());
+    let q = auth_impl.zkp().q().clone();
+    let addr = spawn_server(auth_impl).await;
+    let mut client = connect(addr).await;
+
+    let request = RegisterRequest {
+        user: "alice".to_string(),
+        y1: vec![2],
+        y2: vec![3],
+    };
+    client.register(request).await.unwrap();
+
+    let request = AuthenticationChallengeRequest {
+        user: "alice".to_string(),
+        r1: vec![8],
+        r2: vec![4],
+    };
+    let response = client.create_authentication_challenge(request).await.unwrap().into_inner();
+    assert!(!response.auth_id.is_empty());
+    assert!(BigUint::from_bytes_be(&response.c) < q);
+
+    let request = AuthenticationChallengeRequest {
+        user: "bob".to_string(),
+        r1: vec![8],
+        r2: vec![4],
+    };
+    let status = client.create_authentication_challenge(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_verify_authentication() {
+    let zkp = ZKP::default_1024();
+    let key = b"session key".to_vec();
+    let addr = spawn_server(AuthImpl::default().with_session_key(key.clone())).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+
+    // tampered solution is denied
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    let s = zkp.solve(&k, &c, &x).unwrap();
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: (&s + BigUint::from(1u32)).to_bytes_be(),
+    };
+    let status = client.verify_authentication(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    let s = zkp.solve(&k, &c, &x).unwrap();
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: s.to_bytes_be(),
+    };
+    let response = client.verify_authentication(request).await.unwrap().into_inner();
+    let sessions = SessionSigner::new(key, DEFAULT_SESSION_TTL);
+    assert_eq!(sessions.validate(&response.session_id), Ok("alice".to_string()));
+
+    let request = AuthenticationAnswerRequest {
+        auth_id: "unknown".to_string(),
+        s: s.to_bytes_be(),
+    };
+    let status = client.verify_authentication(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_expired_challenge_is_rejected() {
+    let zkp = ZKP::default_1024();
+    let addr = spawn_server(AuthImpl::default().with_challenge_ttl(Duration::from_millis(50))).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+
+    tokio::time::sleep(Duration::from_millis(100)).await;
+
+    let request = AuthenticationAnswerRequest {
+        auth_id: auth_id.clone(),
+        s: zkp.solve(&k, &c, &x).unwrap().to_bytes_be(),
+    };
+    let status = client.verify_authentication(request.clone()).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::DeadlineExceeded);
+
+    // the expired challenge is pruned
+    let status = client.verify_authentication(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_challenge_cannot_be_replayed() {
+    let zkp = ZKP::default_1024();
+    let addr = spawn_server(AuthImpl::default()).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: zkp.solve(&k, &c, &x).unwrap().to_bytes_be(),
+    };
+    assert!(client.verify_authentication(request.clone()).await.is_ok());
+
+    let status = client.verify_authentication(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::NotFound);
+}
+
+#[tokio::test]
+async fn test_concurrent_challenges_for_one_user() {
+    let zkp = ZKP::default_1024();
+    let addr = spawn_server(AuthImpl::default()).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+
+    // two devices open a challenge before either answers
+    let (auth_id_a, k_a, c_a) = challenge(&mut client, &zkp, "alice").await;
+    let (auth_id_b, k_b, c_b) = challenge(&mut client, &zkp, "alice").await;
+    assert_ne!(auth_id_a, auth_id_b);
+
+    let request = AuthenticationAnswerRequest {
+        auth_id: auth_id_b,
+        s: zkp.solve(&k_b, &c_b, &x).unwrap().to_bytes_be(),
+    };
+    let session_b = client.verify_authentication(request).await.unwrap().into_inner().session_id;
+
+    let request = AuthenticationAnswerRequest {
+        auth_id: auth_id_a,
+        s: zkp.solve(&k_a, &c_a, &x).unwrap().to_bytes_be(),
+    };
+    let session_a = client.verify_authentication(request).await.unwrap().into_inner().session_id;
+
+    assert_ne!(session_a, session_b);
+}
+
+#[tokio::test]
+async fn test_proof_for_one_user_is_rejected_for_another() {
+    let zkp = ZKP::default_1024();
+    let key = b"challenge key".to_vec();
+    let auth_impl = Arc::new(AuthImpl::default().with_keyed_challenges(key.clone()));
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(router(auth_impl.clone()).await.serve_with_incoming(TcpListenerStream::new(listener)));
+    let mut client = connect(addr).await;
+
+    // same x, so alice and bob have the same y1, y2
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+    register(&mut client, &zkp, "bob", &x).await;
+
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    let s = zkp.solve(&k, &c, &x).unwrap();
+    let answer = || AuthenticationAnswerRequest { auth_id: auth_id.clone(), s: s.to_bytes_be() };
+
+    // the same challenge moved to bob on a server holding the key: a keyed c
+    // is recomputed from the key, auth id, issue time and the salt of the user,
+    // so only bob's salt keeps alice's answer from passing for him
+    let mut state = auth_impl.export_state().unwrap();
+    state.challenges[0].user = "bob".to_string();
+    let mut as_bob = AuthImpl::default().with_keyed_challenges(key);
+    as_bob.import_state(state).unwrap();
+    let mut bob_client = connect(spawn_server(as_bob).await).await;
+    let status = bob_client.verify_authentication(answer()).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+
+    assert!(client.verify_authentication(answer()).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_legacy_group_is_accepted_during_migration() {
+    let window = Duration::from_millis(500);
+    let auth_impl = AuthImpl::default().with_zkp(ZKP::default_2048()).with_legacy_zkp(ZKP::default_1024(), window);
+    let addr = spawn_server(auth_impl).await;
+
+    // alice registered before the migration, bob after it
+    let mut alice = auth_client::AuthClient::new(format!("http://{}", addr)).with_zkp(ZKP::default_1024()).with_retries(0);
+    let mut bob = auth_client::AuthClient::new(format!("http://{}", addr)).with_zkp(ZKP::default_2048()).with_retries(0);
+    let alice_x = Secret::new(alice.zkp().generate_challenge());
+    let bob_x = Secret::new(bob.zkp().generate_challenge());
+    alice.register("alice", &alice_x).await.unwrap();
+    bob.register("bob", &bob_x).await.unwrap();
+
+    assert!(alice.authenticate("alice", &alice_x).await.is_ok());
+    assert!(bob.authenticate("bob", &bob_x).await.is_ok());
+
+    // challenges are 2048-bit group scalars again, too wide for a 1024-bit client
+    tokio::time::sleep(window).await;
+    assert!(matches!(alice.authenticate("alice", &alice_x).await, Err(ZKPError::InvalidParameter(_))));
+    // and the server no longer tries the legacy group for a proof that ignores that
+    let legacy = ZKP::default_1024();
+    let mut client = connect(addr).await;
+    let (auth_id, k, c) = challenge(&mut client, &legacy, "alice").await;
+    let s = legacy.solve(&k, &c, &alice_x).unwrap();
+    let status = client.verify_authentication(AuthenticationAnswerRequest { auth_id, s: s.to_bytes_be() }).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    assert!(bob.authenticate("bob", &bob_x).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_serve_with_shutdown_exits() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("users.json");
+    let auth_impl = Arc::new(AuthImpl::with_user_store(Box::new(JsonFileUserStore::open(&path).unwrap())));
+
+    let (tx, rx) = oneshot::channel::<()>();
+    let addr = "127.0.0.1:0".parse().unwrap();
+    let server = tokio::spawn(serve_with_shutdown(auth_impl, addr, async {
+        rx.await.ok();
+    }));
+
+    tx.send(()).unwrap();
+    let result = tokio::time::timeout(Duration::from_secs(5), server).await.expect("server did not shut down");
+    assert_eq!(result.unwrap(), Ok(()));
+
+    // the store was flushed on the way out
+    assert!(path.exists());
+}
+
+#[tokio::test]
+async fn test_failed_verifications_are_rate_limited() {
+    let zkp = ZKP::default_1024();
+    let addr = spawn_server(AuthImpl::default().with_rate_limit(2, Duration::from_secs(60))).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+    register(&mut client, &zkp, "bob", &x).await;
+
+    let x_fake = &x + BigUint::from(1u32);
+    for _ in 0..2 {
+        let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+        let request = AuthenticationAnswerRequest {
+            auth_id,
+            s: zkp.solve(&k, &c, &x_fake).unwrap().to_bytes_be(),
+        };
+        let status = client.verify_authentication(request).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::PermissionDenied);
+    }
+
+    // even a correct answer is throttled now
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: zkp.solve(&k, &c, &x).unwrap().to_bytes_be(),
+    };
+    let status = client.verify_authentication(request).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::ResourceExhausted);
+
+    // other users are not affected
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "bob").await;
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: zkp.solve(&k, &c, &x).unwrap().to_bytes_be(),
+    };
+    assert!(client.verify_authentication(request).await.is_ok());
+}
+
+#[tokio::test]
+async fn test_error_details_distinguish_failures() {
+    let zkp = ZKP::default_1024();
+    let addr = spawn_server(AuthImpl::default().with_challenge_ttl(Duration::from_millis(50))).await;
+    let mut client = connect(addr).await;
+
+    let x = ZKP::generate_rand_below(zkp.q());
+    register(&mut client, &zkp, "alice", &x).await;
+
+    // wrong proof
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: zkp.solve(&k, &c, &(&x + BigUint::from(1u32))).unwrap().to_bytes_be(),
+    };
+    let wrong_proof = client.verify_authentication(request).await.unwrap_err();
+
+    // expired challenge
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    let request = AuthenticationAnswerRequest {
+        auth_id,
+        s: zkp.solve(&k, &c, &x).unwrap().to_bytes_be(),
+    };
+    let expired = client.verify_authentication(request).await.unwrap_err();
+
+    assert_eq!(error_code(&wrong_proof), Some("verification-failed"));
+    assert!(!is_retryable(&wrong_proof));
+    assert_eq!(error_code(&expired), Some("challenge-expired"));
+    assert!(is_retryable(&expired));
+}
+
+#[tokio::test]
+async fn test_auth_client_register_and_authenticate() {
+    let addr = spawn_server(AuthImpl::default()).await;
+    let mut client = auth_client::AuthClient::new(format!("http://{}", addr));
+
+    let x = Secret::new(ZKP::generate_rand_below(client.zkp().q()));
+    client.register("alice", &x).await.unwrap();
+    assert_eq!(client.register("alice", &x).await, Err(ZKPError::UserAlreadyExists("alice".to_string())));
+
+    let session_id = client.authenticate("alice", &x).await.unwrap();
+    assert!(!session_id.is_empty());
+
+    // a wrong secret is not retried
+    let wrong_x = Secret::new(&*x + BigUint::from(1u32));
+    assert_eq!(client.authenticate("alice", &wrong_x).await, Err(ZKPError::VerificationFailed));
+    assert_eq!(client.authenticate("bob", &x).await, Err(ZKPError::UserNotFound("bob".to_string())));
+}
+
+#[tokio::test]
+async fn test_auth_client_reconnects() {
+    // reserve a port, nothing listens on it until the server starts below
+    let addr = TcpListener::bind("127.0.0.1:0").await.unwrap().local_addr().unwrap();
+
+    let mut client = auth_client::AuthClient::new(format!("http://{}", addr))
+        .with_retries(0)
+        .with_retry_delay(Duration::from_millis(50));
+    let x = Secret::new(ZKP::generate_rand_below(client.zkp().q()));
+    assert!(matches!(client.register("alice", &x).await, Err(ZKPError::Transport(_))));
+
+    let mut client = client.with_retries(20);
+    let server = tokio::spawn(async move {
+        tokio::time::sleep(Duration::from_millis(200)).await;
+        Server::builder()
+            .add_service(AuthServer::new(AuthImpl::default()))
+            .serve(addr)
+            .await
+            .unwrap();
+    });
+
+    client.register("alice", &x).await.unwrap();
+    assert!(client.authenticate("alice", &x).await.is_ok());
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_auth_client_times_out() {
+    // accepts connections and never reads from or writes to them
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let server = tokio::spawn(async move {
+        let mut sockets = Vec::new();
+        while let Ok((socket, _)) = listener.accept().await {
+            sockets.push(socket);
+        }
+    });
+
+    let timeout = Duration::from_millis(200);
+    let mut client = auth_client::AuthClient::new(format!("http://{}", addr)).with_retries(0).with_timeout(timeout);
+    let x = Secret::new(ZKP::generate_rand_below(client.zkp().q()));
+
+    let start = std::time::Instant::now();
+    assert_eq!(client.register("alice", &x).await, Err(ZKPError::Timeout("register".to_string())));
+    assert_eq!(client.authenticate("alice", &x).await, Err(ZKPError::Timeout("create_authentication_challenge".to_string())));
+    assert!(start.elapsed() < timeout * 5);
+
+    // retries start over on a new connection and time out as well
+    let mut client = client.with_retries(2);
+    let start = std::time::Instant::now();
+    assert!(matches!(client.register("alice", &x).await, Err(ZKPError::Timeout(_))));
+    assert!(start.elapsed() >= timeout * 3);
+    server.abort();
+}
+
+#[tokio::test]
+async fn test_health_reports_serving() {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = router(Arc::new(AuthImpl::default())).await;
+    tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    let channel = Channel::from_shared(format!("http://{}", addr)).unwrap().connect().await.unwrap();
+    let mut health = HealthClient::new(channel);
+    for service in ["", "zkp_auth.Auth"] {
+        let request = HealthCheckRequest { service: service.to_string() };
+        let response = health.check(request).await.unwrap().into_inner();
+        assert_eq!(response.status(), ServingStatus::Serving);
+    }
+}
+
+#[tokio::test]
+async fn test_stateful_and_stateless_verification() {
+    let zkp = ZKP::default_1024();
+    let x = ZKP::generate_rand_below(zkp.q());
+    let (y1, y2) = zkp.register_commitment(&x);
+    let register_request = |user: &str| RegisterRequest {
+        user: user.to_string(),
+        y1: y1.to_bytes_be(),
+        y2: y2.to_bytes_be(),
+    };
+    let answer = |auth_id: String, k: &BigUint, c: &BigUint, token: Option<&str>| {
+        let s = zkp.solve(k, c, &x).unwrap();
+        let mut request = tonic::Request::new(AuthenticationAnswerRequest { auth_id, s: s.to_bytes_be() });
+        if let Some(token) = token {
+            request.metadata_mut().insert(REGISTRATION_TOKEN_KEY, token.parse().unwrap());
+        }
+        request
+    };
+
+    // stateful: y1, y2 come from the user store, no token is handed out
+    let mut client = connect(spawn_server(AuthImpl::default()).await).await;
+    let response = client.register(register_request("alice")).await.unwrap().into_inner();
+    assert!(response.registration_token.is_empty());
+    let (auth_id, k, c) = challenge(&mut client, &zkp, "alice").await;
+    assert!(client.verify_authentication(answer(auth_id, &k, &c, None)).await.is_ok());
+
+    // stateless: y1, y2 and the salt come from the token sent with the
+    // challenge request and with the answer
+    let mut client = connect(spawn_server(AuthImpl::default().with_registration_tokens(b"registration key".to_vec())).await).await;
+    let token = client.register(register_request("alice")).await.unwrap().into_inner().registration_token;
+    assert!(!token.is_empty());
+    let bob_token = client.register(register_request("bob")).await.unwrap().into_inner().registration_token;
+
+    let (auth_id, k, c) = challenge_with_token(&mut client, &zkp, "alice", Some(&token)).await.unwrap();
+    assert!(client.verify_authentication(answer(auth_id, &k, &c, Some(&token))).await.is_ok());
+
+    for token in [None, Some(bob_token.as_str())] {
+        let status = challenge_with_token(&mut client, &zkp, "alice", token).await.unwrap_err();
+        assert_eq!(status.code(), tonic::Code::Unauthenticated);
+    }
+
+    let (auth_id, k, c) = challenge_with_token(&mut client, &zkp, "alice", Some(&token)).await.unwrap();
+    let status = client.verify_authentication(answer(auth_id, &k, &c, None)).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    let (auth_id, k, c) = challenge_with_token(&mut client, &zkp, "alice", Some(&token)).await.unwrap();
+    let status = client.verify_authentication(answer(auth_id, &k, &c, Some(&bob_token))).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+
+    // token signed by another server
+    let forged = AuthImpl::default().with_registration_tokens(b"other key".to_vec());
+    let mut other = connect(spawn_server(forged).await).await;
+    let forged_token = other.register(register_request("alice")).await.unwrap().into_inner().registration_token;
+    let (auth_id, k, c) = challenge_with_token(&mut client, &zkp, "alice", Some(&token)).await.unwrap();
+    let status = client.verify_authentication(answer(auth_id, &k, &c, Some(&forged_token))).await.unwrap_err();
+    assert_eq!(status.code(), tonic::Code::Unauthenticated);
+}
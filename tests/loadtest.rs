@@ -0,0 +1,94 @@
+//! Concurrent register/authenticate cycles against one server, run with
+//! `cargo test --release --features loadtest --test loadtest -- --nocapture`
+//!
+//! ZKP_LOADTEST_CLIENTS sets the number of clients, 64 by default. Runs once
+//! with `verify` on the blocking pool and once inline on the worker threads,
+//! see `AuthImpl::with_inline_verification`, and prints the total time and the
+//! p50/p99 latency of one register/authenticate cycle for each
+//!
+//! Three release runs on a single core, where clients and server share the core:
+//!
+//! | clients | p99 blocking pool | p99 inline    |
+//! |---------|-------------------|---------------|
+//! | 64      | 118-119ms         | 106-108ms     |
+//! | 256     | 1.04-1.21s        | 0.52-1.20s    |
+//!
+//! So the blocking pool does not improve p99 on one core, inline is as fast or
+//! faster. Whether it does on a multi-core machine is still to be measured
+#![cfg(feature = "loadtest")]
+
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use zkp_chaum_pedersen::auth_client::AuthClient;
+use zkp_chaum_pedersen::service::{router, AuthImpl};
+use tokio::task::JoinHandle;
+use zkp_chaum_pedersen::{Secret, ZKPError, ZKP};
+
+const DEFAULT_CLIENTS: usize = 64;
+
+fn clients() -> usize {
+    match std::env::var("ZKP_LOADTEST_CLIENTS") {
+        Ok(value) => value.parse().expect("ZKP_LOADTEST_CLIENTS must be a number"),
+        Err(_) => DEFAULT_CLIENTS,
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_concurrent_clients() {
+    let n = clients();
+    for (mode, auth_impl) in [("blocking pool", AuthImpl::default()), ("inline", AuthImpl::default().with_inline_verification())] {
+        let (elapsed, latencies) = run(auth_impl, n).await;
+        let percentile = |p: usize| latencies[(latencies.len() * p / 100).min(latencies.len() - 1)];
+        println!("{}: {} register/authenticate cycles in {:?}, latency p50 {:?} p99 {:?}", mode, n, elapsed, percentile(50), percentile(99));
+    }
+}
+
+/// `n` clients registering and authenticating at once against a fresh server
+/// for `auth_impl`, output = total time and the sorted latencies of one cycle
+async fn run(auth_impl: AuthImpl, n: usize) -> (Duration, Vec<Duration>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let router = router(Arc::new(auth_impl)).await;
+    let server = tokio::spawn(router.serve_with_incoming(TcpListenerStream::new(listener)));
+
+    let start = Instant::now();
+
+    let handles: Vec<_> = (0..n)
+        .map(|i| {
+            tokio::spawn(async move {
+                let mut client = AuthClient::new(format!("http://{}", addr));
+                let user = format!("user-{}", i);
+                let x = Secret::new(ZKP::generate_rand_below(client.zkp().q()));
+
+                let start = Instant::now();
+                client.register(&user, &x).await?;
+                client.authenticate(&user, &x).await?;
+                Ok(start.elapsed())
+            })
+        })
+        .collect();
+
+    // a deadlock in the stores would hang here instead of failing
+    let results = tokio::time::timeout(Duration::from_secs(300), join_all(handles)).await.expect("clients did not finish in time");
+    let elapsed = start.elapsed();
+    server.abort();
+
+    let (latencies, failures): (Vec<_>, Vec<_>) = results.into_iter().partition(Result::is_ok);
+    assert!(failures.is_empty(), "{} of {} clients failed: {:?}", failures.len(), n, failures);
+
+    let mut latencies: Vec<Duration> = latencies.into_iter().map(Result::unwrap).collect();
+    latencies.sort();
+    (elapsed, latencies)
+}
+
+/// Waits for every client in order, panics in a client fail the test
+async fn join_all<T>(handles: Vec<JoinHandle<Result<T, ZKPError>>>) -> Vec<Result<T, ZKPError>> {
+    let mut results = Vec::with_capacity(handles.len());
+    for handle in handles {
+        results.push(handle.await.expect("client task panicked"));
+    }
+    results
+}
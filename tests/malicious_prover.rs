@@ -0,0 +1,122 @@
+#![cfg(feature = "std")]
+
+use num_bigint::BigUint;
+use rand::SeedableRng;
+use rand_chacha::ChaCha20Rng;
+use zkp_chaum_pedersen::{Commitment, Proof, VerifyOutcome, ZKP};
+
+/// (r1, r2, y1, y2, c, s) as passed to `ZKP::verify`
+type Transcript = (BigUint, BigUint, BigUint, BigUint, BigUint, BigUint);
+
+/// Every verification entry point must reject `transcript`
+fn assert_rejected(zkp: &ZKP, case: &str, (r1, r2, y1, y2, c, s): &Transcript) {
+    assert!(!zkp.verify(r1, r2, y1, y2, c, s), "verify accepted {}", case);
+    assert!(!zkp.verify_constant_time(r1, r2, y1, y2, c, s), "verify_constant_time accepted {}", case);
+    assert_ne!(zkp.verify_detailed(r1, r2, y1, y2, c, s), VerifyOutcome::Ok, "verify_detailed accepted {}", case);
+}
+
+/// Prover that knows x, or pretends to, and deviates from the protocol one way at a time
+struct MaliciousProver<'a> {
+    zkp: &'a ZKP,
+    rng: ChaCha20Rng,
+}
+
+impl MaliciousProver<'_> {
+    fn scalar(&mut self) -> BigUint {
+        self.zkp.generate_challenge_with(&mut self.rng)
+    }
+
+    /// Honest transcript for a fresh x, k and c, the starting point of every case
+    fn honest(&mut self) -> (BigUint, BigUint, Transcript) {
+        let (x, k, c) = (self.scalar(), self.scalar(), self.scalar());
+        let (y1, y2) = self.zkp.register_commitment(&x);
+        let (r1, r2, s) = self.zkp.prove(&x, &k, &c).unwrap();
+        (x, k, (r1, r2, y1, y2, c, s))
+    }
+
+    /// Transcripts that all have to be rejected, with a name for the assert message
+    fn cases(&mut self) -> Vec<(&'static str, Transcript)> {
+        let zkp = self.zkp;
+        let one = BigUint::from(1u32);
+        let mut cases = Vec::new();
+
+        let (_, _, (r1, r2, y1, y2, c, s)) = self.honest();
+        cases.push(("s + 1", (r1.clone(), r2.clone(), y1.clone(), y2.clone(), c.clone(), (&s + &one) % zkp.q())));
+        cases.push(("s + q", (r1.clone(), r2.clone(), y1.clone(), y2.clone(), c.clone(), &s + zkp.q())));
+        cases.push(("swapped r1 and r2", (r2.clone(), r1.clone(), y1.clone(), y2.clone(), c.clone(), s.clone())));
+        cases.push(("swapped y1 and y2", (r1.clone(), r2.clone(), y2.clone(), y1.clone(), c.clone(), s.clone())));
+        cases.push(("r1 + p", (&r1 + zkp.p(), r2.clone(), y1.clone(), y2.clone(), c.clone(), s.clone())));
+
+        // s answers another challenge than the one checked
+        let (_, _, (r1, r2, y1, y2, c, s)) = self.honest();
+        let other_c = (&c + &one) % zkp.q();
+        cases.push(("c mismatched from the response", (r1, r2, y1, y2, other_c, s)));
+
+        // r1, r2 and k of a proof for x reused to answer with a new secret
+        let (x, k, (r1, r2, y1, y2, c, _)) = self.honest();
+        let new_x = (&x + &one) % zkp.q();
+        let s = zkp.solve(&k, &c, &new_x).unwrap();
+        cases.push(("reused commitment with a new secret", (r1, r2, y1, y2, c, s)));
+
+        // honest proof for one user checked against another user's y1, y2
+        let (x, _, (r1, r2, _, _, c, s)) = self.honest();
+        let (y1, y2) = zkp.register_commitment(&((&x + &one) % zkp.q()));
+        cases.push(("proof for another user", (r1, r2, y1, y2, c, s)));
+
+        // without x the best a prover can do is guess c before committing:
+        // r1 = alpha^s * y1^c_guess passes for c_guess but not for the c it gets
+        let (_, _, (_, _, y1, y2, c, _)) = self.honest();
+        // c_guess in [1, q) and != c, verify rejects c = 0 on its own
+        let (c_guess, s) = (&c % (zkp.q() - &one) + &one, self.scalar());
+        let r1 = (zkp.alpha().modpow(&s, zkp.p()) * y1.modpow(&c_guess, zkp.p())) % zkp.p();
+        let r2 = (zkp.beta().modpow(&s, zkp.p()) * y2.modpow(&c_guess, zkp.p())) % zkp.p();
+        assert!(zkp.verify(&r1, &r2, &y1, &y2, &c_guess, &s));
+        cases.push(("commitment forged for a guessed challenge", (r1, r2, y1, y2, c, s)));
+
+        // y1 and y2 with different discrete logs, x and x + 1
+        let (_, _, (r1, r2, y1, y2, c, s)) = self.honest();
+        let y2_fake = (&y2 * zkp.beta()) % zkp.p();
+        cases.push(("y2 with another discrete log", (r1, r2, y1, y2_fake, c, s)));
+
+        cases
+    }
+}
+
+#[test]
+fn test_malicious_proofs_are_rejected() {
+    for (zkp, seed) in [(ZKP::toy(), 1), (ZKP::default_1024(), 2), (ZKP::default_2048(), 3)] {
+        let mut prover = MaliciousProver{zkp: &zkp, rng: ChaCha20Rng::seed_from_u64(seed)};
+
+        for _ in 0..4 {
+            for (case, transcript) in prover.cases() {
+                assert_rejected(&zkp, case, &transcript);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tampered_noninteractive_proofs_are_rejected() {
+    let zkp = ZKP::default_1024();
+    let mut rng = ChaCha20Rng::seed_from_u64(4);
+    let x = zkp.generate_challenge_with(&mut rng);
+    let k = zkp.generate_challenge_with(&mut rng);
+    let (y1, y2) = zkp.register_commitment(&x);
+    let commitment = Commitment{y1, y2};
+
+    let proof = zkp.prove_noninteractive(&x, &k).unwrap();
+    assert!(zkp.verify_noninteractive(&commitment, &proof));
+
+    // c no longer matches the hash of the commitments, even with s recomputed for it
+    let c = (&proof.c + BigUint::from(1u32)) % zkp.q();
+    let s = zkp.solve(&k, &c, &x).unwrap();
+    let tampered = Proof{c, s, ..proof.clone()};
+    assert!(zkp.verify(&tampered.r1, &tampered.r2, &commitment.y1, &commitment.y2, &tampered.c, &tampered.s));
+    assert!(!zkp.verify_noninteractive(&commitment, &tampered));
+
+    let swapped = Proof{r1: proof.r2.clone(), r2: proof.r1.clone(), ..proof.clone()};
+    assert!(!zkp.verify_noninteractive(&commitment, &swapped));
+
+    let other = Commitment{y1: commitment.y2.clone(), y2: commitment.y1.clone()};
+    assert!(!zkp.verify_noninteractive(&other, &proof));
+}
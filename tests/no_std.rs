@@ -0,0 +1,21 @@
+//! Builds against the crate without default features, run with
+//! `cargo test --no-default-features --test no_std`
+#![cfg(not(feature = "std"))]
+
+use num_bigint::BigUint;
+use zkp_chaum_pedersen::core::{exponentiate, solve, verify};
+
+#[test]
+fn test_core_without_std() {
+    let (p, q) = (BigUint::from(23u32), BigUint::from(11u32));
+    let (alpha, beta) = (BigUint::from(4u32), BigUint::from(9u32));
+    let (x, k, c) = (BigUint::from(6u32), BigUint::from(7u32), BigUint::from(4u32));
+
+    let (y1, y2) = (exponentiate(&alpha, &x, &p), exponentiate(&beta, &x, &p));
+    let (r1, r2) = (exponentiate(&alpha, &k, &p), exponentiate(&beta, &k, &p));
+    let s = solve(&k, &c, &x, &q).unwrap();
+
+    assert!(verify(&p, &q, &alpha, &y1, &r1, &c, &s));
+    assert!(verify(&p, &q, &beta, &y2, &r2, &c, &s));
+    assert!(!verify(&p, &q, &alpha, &y1, &r1, &c, &(s + 1u32)));
+}
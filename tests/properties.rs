@@ -0,0 +1,53 @@
+#![cfg(feature = "std")]
+
+use num_bigint::BigUint;
+use proptest::prelude::*;
+use zkp_chaum_pedersen::ZKP;
+
+/// Uniform-ish scalar in [0, q) for the 1024-bit group
+fn scalar() -> impl Strategy<Value = BigUint> {
+    // 32 bytes reduced mod the 160-bit q keeps the bias negligible
+    proptest::collection::vec(any::<u8>(), 32).prop_map(|bytes| BigUint::from_bytes_be(&bytes) % ZKP::default_1024().q())
+}
+
+/// Scalar in [1, q), x = 0 or c = 0 make a wrong witness or challenge indistinguishable
+fn nonzero_scalar() -> impl Strategy<Value = BigUint> {
+    scalar().prop_filter("non-zero", |value| *value != BigUint::from(0u32))
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn honest_proof_verifies(x in scalar(), k in scalar(), c in nonzero_scalar()) {
+        let zkp = ZKP::default_1024();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c).unwrap();
+
+        prop_assert!(s < *zkp.q());
+        prop_assert!(zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn wrong_x_fails(x in scalar(), delta in nonzero_scalar(), k in scalar(), c in nonzero_scalar()) {
+        let zkp = ZKP::default_1024();
+        let x_fake = (&x + &delta) % zkp.q();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x_fake, &k, &c).unwrap();
+
+        prop_assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+
+    #[test]
+    fn wrong_c_fails(x in nonzero_scalar(), k in scalar(), c in scalar(), delta in nonzero_scalar()) {
+        let zkp = ZKP::default_1024();
+        let c_fake = (&c + &delta) % zkp.q();
+
+        let (y1, y2) = zkp.register_commitment(&x);
+        let (r1, r2, s) = zkp.prove(&x, &k, &c_fake).unwrap();
+
+        prop_assert!(!zkp.verify(&r1, &r2, &y1, &y2, &c, &s));
+    }
+}
@@ -0,0 +1,38 @@
+//! Runs the wasm wrapper natively with
+//! `cargo test --no-default-features --features wasm --test wasm`,
+//! the build for the browser itself is checked with
+//! `cargo build --lib --no-default-features --features wasm --target wasm32-unknown-unknown`
+#![cfg(feature = "wasm")]
+
+use num_bigint::BigUint;
+use zkp_chaum_pedersen::core::verify;
+use zkp_chaum_pedersen::wasm::{commitments, nonce, prove};
+
+#[test]
+fn test_toy_login() {
+    let (p, q, alpha, beta) = ([23u8], [11u8], [4u8], [9u8]);
+
+    // one byte per element for p = 23
+    assert_eq!(commitments(&p, &alpha, &beta, &[6]), vec![2, 3]);
+    assert_eq!(commitments(&p, &alpha, &beta, &[7]), vec![8, 4]);
+    assert_eq!(prove(&q, &[6], &[7], &[4]).unwrap(), vec![5]);
+
+    let k = nonce(&q).unwrap();
+    let k_value = BigUint::from_bytes_be(&k);
+    assert!(k_value > BigUint::from(0u32) && k_value < BigUint::from(11u32));
+
+    let rs = commitments(&p, &alpha, &beta, &k);
+    let s = prove(&q, &[6], &k, &[4]).unwrap();
+    let [p, q, alpha, beta, r1, r2, s] = [&p[..], &q, &alpha, &beta, &rs[..1], &rs[1..], &s].map(BigUint::from_bytes_be);
+    let c = BigUint::from(4u32);
+
+    assert!(verify(&p, &q, &alpha, &BigUint::from(2u32), &r1, &c, &s));
+    assert!(verify(&p, &q, &beta, &BigUint::from(3u32), &r2, &c, &s));
+}
+
+#[test]
+fn test_commitments_are_padded_to_p() {
+    // p = 2^16 + 1 takes three bytes, y1 = 2 and y2 = 4 are padded to that
+    let p = 65537u32.to_be_bytes();
+    assert_eq!(commitments(&p, &[2], &[4], &[1]), vec![0, 0, 2, 0, 0, 4]);
+}